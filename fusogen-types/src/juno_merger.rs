@@ -0,0 +1,18 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Uint128;
+
+/// Hand back locked source tokens for `amount` of the merged token burned
+/// on XION.
+#[cw_serde]
+pub struct ReleaseMsg {
+    pub recipient: String,
+    pub source_token: String,
+    pub amount: Uint128,
+}
+
+/// The part of juno-merger's execute API that xion-minter calls. It
+/// serializes exactly like the matching juno-merger `ExecuteMsg` variants.
+#[cw_serde]
+pub enum RemoteExecuteMsg {
+    Release(ReleaseMsg),
+}
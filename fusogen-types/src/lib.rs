@@ -0,0 +1,7 @@
+//! Messages the bridge contracts send each other over Polytone. Both sides
+//! build and parse them through these types, so a rename on one side is a
+//! compile error on the other instead of a silently failing packet.
+
+pub mod juno_merger;
+pub mod polytone;
+pub mod xion_minter;
@@ -0,0 +1,85 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{CosmosMsg, Empty, Uint64};
+use polytone::callbacks::CallbackRequest;
+
+/// Execute message of the Polytone note.
+#[cw_serde]
+pub enum PolytoneExecuteMsg {
+    Execute {
+        msgs: Vec<CosmosMsg<Empty>>,
+        callback: Option<CallbackRequest>,
+        timeout_seconds: Uint64,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{from_json, to_json_binary, Binary, Uint128, WasmMsg};
+
+    use crate::juno_merger::{ReleaseMsg, RemoteExecuteMsg};
+    use crate::xion_minter::ExecuteMsg as XionMinterExecuteMsg;
+
+    #[test]
+    fn test_note_execute_matches_workshop_format() {
+        let mint = WasmMsg::Execute {
+            contract_addr: "xion1minter".to_string(),
+            msg: to_json_binary(&XionMinterExecuteMsg::Mint {
+                amount: Uint128::new(1234),
+                recipient: Some("xion1user".to_string()),
+                nonce: 7,
+                source_token: None,
+            })
+            .unwrap(),
+            funds: vec![],
+        };
+        let msg = PolytoneExecuteMsg::Execute {
+            msgs: vec![mint.into()],
+            callback: Some(CallbackRequest {
+                receiver: "juno1merger".to_string(),
+                msg: to_json_binary(&7u64).unwrap(),
+            }),
+            timeout_seconds: Uint64::new(300),
+        };
+
+        // the shape the merger used to assemble by hand
+        let expected: serde_json::Value = serde_json::json!({
+            "execute": {
+                "msgs": [{
+                    "wasm": {
+                        "execute": {
+                            "contract_addr": "xion1minter",
+                            "msg": Binary::from(
+                                br#"{"mint":{"amount":"1234","recipient":"xion1user","nonce":7}}"#
+                            ),
+                            "funds": []
+                        }
+                    }
+                }],
+                "callback": {
+                    "receiver": "juno1merger",
+                    "msg": to_json_binary(&7u64).unwrap()
+                },
+                "timeout_seconds": "300"
+            }
+        });
+        let actual: serde_json::Value =
+            serde_json::from_slice(to_json_binary(&msg).unwrap().as_slice()).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_release_round_trips() {
+        let release = RemoteExecuteMsg::Release(ReleaseMsg {
+            recipient: "juno1user".to_string(),
+            source_token: "juno1tokena".to_string(),
+            amount: Uint128::new(400),
+        });
+        let bin = to_json_binary(&release).unwrap();
+        assert_eq!(
+            bin.as_slice(),
+            br#"{"release":{"recipient":"juno1user","source_token":"juno1tokena","amount":"400"}}"#
+        );
+        assert_eq!(from_json::<RemoteExecuteMsg>(&bin).unwrap(), release);
+    }
+}
@@ -0,0 +1,86 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Uint128;
+use cw20::Cw20ReceiveMsg;
+use polytone::callbacks::CallbackMessage;
+
+/// xion-minter's execute API. juno-merger sends `Mint` through its proxy.
+#[cw_serde]
+pub enum ExecuteMsg {
+    Mint {
+        amount: Uint128,
+        recipient: Option<String>,
+        /// juno-merger's lock id. Each nonce mints at most once, so a
+        /// replayed packet can't mint twice.
+        nonce: u64,
+        /// Juno token that was locked; selects its vesting schedule.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source_token: Option<String>,
+    },
+    /// Several mints in one message. Every entry is checked like a `Mint`
+    /// and if any fails, none of them mint.
+    BatchMint { entries: Vec<MintEntry> },
+    /// Guardian: stop minting.
+    Pause { reason: Option<String> },
+    /// Guardian: resume minting.
+    Unpause {},
+    /// Guardian: authorise the Polytone proxy that executes on behalf of
+    /// `juno_merger`. The proxy must have been instantiated by the voice.
+    BindProxy { proxy: String, juno_merger: String },
+    /// Guardian: replace or remove the mint rate limit.
+    UpdateRateLimit { rate_limit: Option<RateLimit> },
+    /// Guardian: change the reverse bridge settings.
+    UpdateConfig(UpdateConfigMsg),
+    /// Guardian: vest future mints for `source_token` instead of minting
+    /// straight to the recipient. `None` removes the schedule.
+    SetVestingSchedule {
+        source_token: String,
+        schedule: Option<VestingSchedule>,
+    },
+    /// Withdraw everything vested so far.
+    Claim {},
+    /// Merged token `Send` carrying a `ReceiveMsg::Redeem`.
+    Receive(Cw20ReceiveMsg),
+    /// Polytone callback for a redemption's release on Juno. Only accepted
+    /// from the configured note.
+    Callback(CallbackMessage),
+}
+
+/// Fields left as `None` are unchanged.
+#[cw_serde]
+#[derive(Default)]
+pub struct UpdateConfigMsg {
+    pub polytone_note: Option<String>,
+}
+
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Burn the sent merged tokens and release `source_token` on Juno.
+    Redeem {
+        juno_recipient: String,
+        source_token: String,
+    },
+}
+
+#[cw_serde]
+pub struct MintEntry {
+    pub amount: Uint128,
+    pub recipient: String,
+    pub nonce: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_token: Option<String>,
+}
+
+/// Nothing vests until `cliff_seconds` after the mint, then the amount
+/// vests linearly until `duration_seconds` after it.
+#[cw_serde]
+pub struct VestingSchedule {
+    pub cliff_seconds: u64,
+    pub duration_seconds: u64,
+}
+
+/// At most `max_amount` may be minted in any `window_seconds` long window.
+#[cw_serde]
+pub struct RateLimit {
+    pub max_amount: Uint128,
+    pub window_seconds: u64,
+}
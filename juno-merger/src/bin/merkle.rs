@@ -0,0 +1,146 @@
+//! Builds a snapshot allowlist for `SetMerkleRoot` from a CSV of
+//! `address,amount` rows, printing the root and every address's proof as
+//! JSON:
+//!
+//!     cargo run --bin merkle -- balances.csv > allowlist.json
+use std::collections::HashSet;
+use std::{env, fs, process};
+
+use cosmwasm_std::{to_json_string, Uint128};
+use serde::Serialize;
+
+use juno_merger::merkle::{build_layers, build_proof, leaf_hash};
+use juno_merger::msg::EligibilityProof;
+
+#[derive(Serialize)]
+struct Allowlist {
+    root: String,
+    entries: Vec<Entry>,
+}
+
+/// `eligibility` is the `proof` to send with this address's locks.
+#[derive(Serialize)]
+struct Entry {
+    address: String,
+    eligibility: EligibilityProof,
+}
+
+fn main() {
+    let path = match env::args().nth(1) {
+        Some(path) => path,
+        None => fail("usage: merkle <balances.csv>"),
+    };
+    let csv = fs::read_to_string(&path).unwrap_or_else(|err| fail(&format!("{}: {}", path, err)));
+
+    let balances = parse_balances(&csv).unwrap_or_else(|err| fail(&err));
+    if balances.is_empty() {
+        fail("no balances found");
+    }
+
+    let leaves = balances
+        .iter()
+        .map(|(address, amount)| leaf_hash(address, *amount))
+        .collect();
+    let layers = build_layers(leaves);
+    let entries = balances
+        .into_iter()
+        .enumerate()
+        .map(|(index, (address, max_amount))| Entry {
+            address,
+            eligibility: EligibilityProof {
+                max_amount,
+                proof: build_proof(&layers, index)
+                    .iter()
+                    .map(hex::encode)
+                    .collect(),
+            },
+        })
+        .collect();
+
+    let allowlist = Allowlist {
+        root: hex::encode(layers.last().unwrap()[0]),
+        entries,
+    };
+    println!("{}", to_json_string(&allowlist).unwrap());
+}
+
+/// Reads `address,amount` rows, skipping blank lines and an optional
+/// `address,amount` header on the first line.
+fn parse_balances(csv: &str) -> Result<Vec<(String, Uint128)>, String> {
+    let mut seen = HashSet::new();
+    let mut balances = vec![];
+    for (index, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (address, amount) = line
+            .split_once(',')
+            .ok_or_else(|| format!("line {}: expected address,amount", index + 1))?;
+        let (address, amount) = (address.trim(), amount.trim());
+        if index == 0
+            && address.eq_ignore_ascii_case("address")
+            && amount.eq_ignore_ascii_case("amount")
+        {
+            continue;
+        }
+        if address.is_empty() {
+            return Err(format!("line {}: missing address", index + 1));
+        }
+        let address = address.to_lowercase();
+        let amount = amount
+            .parse::<u128>()
+            .map_err(|err| format!("line {}: invalid amount {:?}: {}", index + 1, amount, err))?;
+        if !seen.insert(address.clone()) {
+            return Err(format!("line {}: duplicate address {}", index + 1, address));
+        }
+        balances.push((address, Uint128::new(amount)));
+    }
+    Ok(balances)
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    process::exit(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_balances() {
+        let balances =
+            parse_balances("Address,Amount\n\njuno1alice, 100\n  \nJUNO1BOB,25\n").unwrap();
+        assert_eq!(
+            balances,
+            vec![
+                ("juno1alice".to_string(), Uint128::new(100)),
+                ("juno1bob".to_string(), Uint128::new(25)),
+            ]
+        );
+
+        // only the expected header is skipped, and only on the first line
+        let err = parse_balances("wallet,balance\njuno1alice,100").unwrap_err();
+        assert!(
+            err.starts_with("line 1: invalid amount \"balance\""),
+            "{}",
+            err
+        );
+        let err = parse_balances("juno1alice,100\naddress,amount").unwrap_err();
+        assert!(
+            err.starts_with("line 2: invalid amount \"amount\""),
+            "{}",
+            err
+        );
+
+        let err = parse_balances("juno1alice,100\njuno1bob,1.5").unwrap_err();
+        assert!(err.starts_with("line 2: invalid amount \"1.5\""), "{}", err);
+        let err = parse_balances("juno1alice,100\njuno1bob").unwrap_err();
+        assert_eq!(err, "line 2: expected address,amount");
+
+        // addresses are compared case-insensitively
+        let err = parse_balances("juno1alice,100\nJUNO1ALICE,5").unwrap_err();
+        assert_eq!(err, "line 2: duplicate address juno1alice");
+    }
+}
@@ -1,38 +1,220 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{
-    entry_point, from_json, to_json_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env,
-    MessageInfo, Response, StdError, StdResult, SubMsg, Uint128, Uint64, WasmMsg,
+    coins, entry_point, from_json, to_json_binary, Addr, BankMsg, Binary, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, MessageInfo, Order, Response, StdResult, Timestamp, Uint128, Uint64, WasmMsg,
 };
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
-use base64;
-use serde_json::json;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+use cw_storage_plus::Bound;
+use fusogen_types::xion_minter::{ExecuteMsg as XionMinterExecuteMsg, MintEntry};
+use semver::Version;
 
 use crate::error::ContractError;
-use crate::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, PolytoneExecuteMsg, QueryMsg, ReceiveMsg};
-use crate::state::{Config, CONFIG};
+use crate::merkle::{leaf_hash, verify_proof, Hash};
+use crate::msg::{
+    Approval, ApprovalsResponse, BatchQueueResponse, ConfigResponse, EligibilityProof, ExecuteMsg,
+    FeeCollected, FeesCollectedResponse, FlushCallback, InstantiateMsg, LockResponse,
+    LocksResponse, MigrateMsg, MintCallback, OptionalConfigField, OwnershipResponse, PauseResponse,
+    PhaseResponse, PolytoneExecuteMsg, QueryMsg, ReceiveMsg, ReleaseMsg, SourceTokenMsg,
+    SourceTokenResponse, SourceTokensResponse, UpdateConfigMsg, UserCapacityResponse,
+};
+use crate::state::{
+    locks, Config, Finalization, Lock, LockStatus, MergeMode, MergerPhase, Ownership, PauseState,
+    SourceTokenConfig, TokenKind, APPROVERS, BASKETS, BATCH_QUEUE, CONFIG, CUMULATIVE_LOCKED,
+    FEES_COLLECTED, FINALIZATION, LEGACY_CONFIG, LOCK_COUNT, OWNERSHIP, PAUSE, RELEASABLE,
+    SOURCE_TOKENS, TOTAL_BURNED, TOTAL_LOCKED, USER_LOCKED,
+};
+use polytone::callbacks::{Callback, CallbackMessage, CallbackRequest};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:juno-merger";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// bounds on how long the note waits for XION before reporting a timeout
+const DEFAULT_TIMEOUT_SECONDS: u64 = 300;
+const MIN_TIMEOUT_SECONDS: u64 = 60;
+const MAX_TIMEOUT_SECONDS: u64 = 86_400;
+
+const DEFAULT_XION_HRP: &str = "xion";
+
+// hard ceiling on the lock fee, 10%
+const MAX_FEE_BPS: u16 = 1_000;
+
+// most locks one basket may hold, which bounds the size of a flush packet
+const MAX_BASKET_SIZE: u32 = 10;
+
+// how many queued locks one ProcessBatch mints
+const DEFAULT_BATCH_SIZE: u32 = 20;
+const MAX_BATCH_SIZE: u32 = 50;
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    deps: DepsMut,
+    mut deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender,
+    };
+    let guardian = msg
+        .guardian
+        .map(|guardian| deps.api.addr_validate(&guardian))
+        .transpose()?;
     let config = Config {
+        guardian: guardian.clone(),
         note_contract: deps.api.addr_validate(&msg.note_contract)?,
-        token_a: deps.api.addr_validate(&msg.token_a)?,
-        token_b: deps.api.addr_validate(&msg.token_b)?,
-        xion_mint_contract: msg.xion_mint_contract.clone(),
+        xion_mint_contract: validate_xion_contract(msg.xion_mint_contract.clone())?,
+        xion_proxy: None,
+        timeout_seconds: validate_timeout(msg.timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS))?,
+        xion_hrp: validate_hrp(msg.xion_hrp.as_deref().unwrap_or(DEFAULT_XION_HRP))?,
+        start_time: msg.start_time,
+        end_time: msg.end_time,
+        fee_bps: msg.fee_bps.unwrap_or_default(),
+        treasury: msg
+            .treasury
+            .map(|treasury| deps.api.addr_validate(&treasury))
+            .transpose()?,
+        batching: msg.batching.unwrap_or_default(),
     };
+    validate_window(&config)?;
+    validate_fee(&config)?;
 
     CONFIG.save(deps.storage, &config)?;
+    OWNERSHIP.save(
+        deps.storage,
+        &Ownership {
+            owner: owner.clone(),
+            pending_owner: None,
+        },
+    )?;
 
-    Ok(Response::new()
+    let mut response = Response::new()
         .add_attribute("action", "instantiate")
-        .add_attribute("token_a", msg.token_a)
-        .add_attribute("token_b", msg.token_b)
+        .add_attribute("owner", owner)
+        .add_attribute(
+            "guardian",
+            guardian.map(Addr::into_string).unwrap_or_default(),
+        )
         .add_attribute("note_contract", msg.note_contract)
-        .add_attribute("xion_mint_contract", msg.xion_mint_contract))
+        .add_attribute("xion_mint_contract", msg.xion_mint_contract);
+
+    for source in msg.source_tokens {
+        let token = add_source_token(deps.branch(), source)?;
+        response = response.add_attribute("source_token", token);
+    }
+    for approver in msg.approvers.unwrap_or_default() {
+        let approver = deps.api.addr_validate(&approver)?;
+        APPROVERS.save(deps.storage, &approver, &None)?;
+        response = response.add_attribute("approver", approver);
+    }
+
+    Ok(response)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let response = match get_contract_version(deps.storage) {
+        Ok(previous) => {
+            if previous.contract != CONTRACT_NAME {
+                return Err(ContractError::CannotMigrate {
+                    previous_contract: previous.contract,
+                    previous_version: previous.version,
+                });
+            }
+            let previous_version: Version = previous.version.parse()?;
+            let new_version: Version = CONTRACT_VERSION.parse()?;
+            if previous_version > new_version {
+                return Err(ContractError::CannotMigrate {
+                    previous_contract: previous.contract,
+                    previous_version: previous.version,
+                });
+            }
+            Response::new().add_attribute("previous_version", previous.version)
+        }
+        // deployments made before cw2 versioning stored the two-token config
+        Err(_) => migrate_legacy_config(deps.branch(), &env, msg)?,
+    };
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(response
+        .add_attribute("action", "migrate")
+        .add_attribute("version", CONTRACT_VERSION))
+}
+
+/// Moves a legacy `token_a`/`token_b` config into the source token
+/// registry at a 1:1 ratio and seeds the locked totals from the
+/// contract's balances, so the funds already held stay accounted for.
+fn migrate_legacy_config(
+    deps: DepsMut,
+    env: &Env,
+    msg: MigrateMsg,
+) -> Result<Response, ContractError> {
+    let legacy = LEGACY_CONFIG.load(deps.storage)?;
+    let owner = deps
+        .api
+        .addr_validate(&msg.owner.ok_or(ContractError::MissingOwner {})?)?;
+
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            guardian: None,
+            note_contract: legacy.note_contract,
+            xion_mint_contract: legacy.xion_mint_contract,
+            xion_proxy: None,
+            timeout_seconds: DEFAULT_TIMEOUT_SECONDS,
+            xion_hrp: DEFAULT_XION_HRP.to_string(),
+            start_time: None,
+            end_time: None,
+            fee_bps: 0,
+            treasury: None,
+            batching: false,
+        },
+    )?;
+    OWNERSHIP.save(
+        deps.storage,
+        &Ownership {
+            owner: owner.clone(),
+            pending_owner: None,
+        },
+    )?;
+
+    for token in [legacy.token_a, legacy.token_b] {
+        SOURCE_TOKENS.save(
+            deps.storage,
+            token.as_str(),
+            &SourceTokenConfig {
+                kind: TokenKind::Cw20,
+                ratio: Decimal::one(),
+                cap: None,
+                user_cap: None,
+                mode: MergeMode::Lock,
+                merkle_root: None,
+                enabled: true,
+            },
+        )?;
+        let balance: cw20::BalanceResponse = deps.querier.query_wasm_smart(
+            &token,
+            &Cw20QueryMsg::Balance {
+                address: env.contract.address.to_string(),
+            },
+        )?;
+        TOTAL_LOCKED.save(deps.storage, token.as_str(), &balance.balance)?;
+        CUMULATIVE_LOCKED.save(deps.storage, token.as_str(), &balance.balance)?;
+        RELEASABLE.save(deps.storage, token.as_str(), &balance.balance)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("previous_version", "legacy")
+        .add_attribute("owner", owner))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -44,304 +226,4203 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Receive(cw20_receive) => receive_cw20(deps, env, info, cw20_receive),
+        ExecuteMsg::Lock {
+            xion_meta_account,
+            proof,
+        } => lock_native(
+            deps,
+            env,
+            info,
+            ReceiveMsg::Lock {
+                xion_meta_account,
+                proof,
+            },
+        ),
+        ExecuteMsg::Deposit {
+            xion_meta_account,
+            proof,
+        } => lock_native(
+            deps,
+            env,
+            info,
+            ReceiveMsg::Deposit {
+                xion_meta_account,
+                proof,
+            },
+        ),
+        ExecuteMsg::Flush {} => flush(deps, env, info),
+        ExecuteMsg::ProcessBatch { limit } => process_batch(deps, env, limit),
+        ExecuteMsg::Callback(callback) => handle_callback(deps, env, info, callback),
+        ExecuteMsg::Release(ReleaseMsg {
+            recipient,
+            source_token,
+            amount,
+        }) => release(deps, info, recipient, source_token, amount),
+        ExecuteMsg::AddSourceToken(source) => {
+            assert_owner(deps.as_ref(), &info)?;
+            let token = add_source_token(deps, source)?;
+            Ok(Response::new()
+                .add_attribute("action", "add_source_token")
+                .add_attribute("source_token", token))
+        }
+        ExecuteMsg::DisableSourceToken { token } => {
+            set_source_token_enabled(deps, info, token, false)
+        }
+        ExecuteMsg::EnableSourceToken { token } => {
+            set_source_token_enabled(deps, info, token, true)
+        }
+        ExecuteMsg::SetSourceTokenCaps {
+            token,
+            cap,
+            user_cap,
+        } => set_source_token_caps(deps, info, token, cap, user_cap),
+        ExecuteMsg::SetMergeMode { token, mode } => set_merge_mode(deps, info, token, mode),
+        ExecuteMsg::SetMerkleRoot { token, root } => set_merkle_root(deps, info, token, root),
+        ExecuteMsg::RemoveSourceToken { token } => remove_source_token(deps, info, token),
+        ExecuteMsg::UpdateConfig(update) => update_config(deps, info, update),
+        ExecuteMsg::TransferOwnership { new_owner } => transfer_ownership(deps, info, new_owner),
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, info),
+        ExecuteMsg::Pause { reason } => set_paused(deps, info, true, reason),
+        ExecuteMsg::Unpause {} => set_paused(deps, info, false, None),
+        ExecuteMsg::Approve {} => approve(deps, env, info),
+        ExecuteMsg::SetApprovers { approvers } => set_approvers(deps, env, info, approvers),
+        ExecuteMsg::Finalize {} => finalize(deps, env),
     }
 }
 
-pub fn receive_cw20(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    cw20_msg: Cw20ReceiveMsg,
-) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
+fn assert_owner(deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
+    let ownership = OWNERSHIP.load(deps.storage)?;
+    if info.sender != ownership.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
 
-    if info.sender != config.token_a && info.sender != config.token_b {
-        return Err(ContractError::InvalidToken {});
+/// The XION contract can't be validated against this chain's address
+/// format, so only reject values that could never be an address.
+fn validate_xion_contract(addr: String) -> Result<String, ContractError> {
+    if addr.is_empty() || addr.chars().any(char::is_whitespace) {
+        return Err(ContractError::InvalidConfig {
+            reason: format!("invalid xion_mint_contract {:?}", addr),
+        });
     }
+    Ok(addr)
+}
 
-    let hook: ReceiveMsg = from_json(&cw20_msg.msg)?;
+fn validate_timeout(timeout_seconds: u64) -> Result<u64, ContractError> {
+    if !(MIN_TIMEOUT_SECONDS..=MAX_TIMEOUT_SECONDS).contains(&timeout_seconds) {
+        return Err(ContractError::InvalidTimeout {
+            min: MIN_TIMEOUT_SECONDS,
+            max: MAX_TIMEOUT_SECONDS,
+        });
+    }
+    Ok(timeout_seconds)
+}
 
-    match hook {
-        ReceiveMsg::Lock { xion_meta_account } => {
-            // Format the mint message and base64 encode it
-            let msg_str = format!(
-                r#"{{"mint":{{"recipient":"{}","amount":"{}"}}}}"#,
-                xion_meta_account, cw20_msg.amount
-            );
-            let base64_msg = base64::encode(msg_str);
-
-            // Create the polytone message exactly like the workshop
-            let execute_msg = json!({
-                "execute": {
-                    "msgs": [{
-                        "wasm": {
-                            "execute": {
-                                "contract_addr": config.xion_mint_contract,
-                                "msg": base64_msg,
-                                "funds": []
-                            }
-                        }
-                    }],
-                    "callback": {
-                        "receiver": env.contract.address.to_string(),
-                        "msg": base64::encode("mint_complete")
-                    },
-                    "timeout_seconds": "300"
-                }
-            });
+fn validate_hrp(hrp: &str) -> Result<String, ContractError> {
+    match bech32::Hrp::parse(hrp) {
+        Ok(parsed) if parsed.as_str() == hrp && hrp == hrp.to_lowercase() => Ok(hrp.to_string()),
+        _ => Err(ContractError::InvalidConfig {
+            reason: format!("invalid xion_hrp {:?}", hrp),
+        }),
+    }
+}
 
-            // Send to note contract
-            let note_msg = WasmMsg::Execute {
-                contract_addr: config.note_contract.to_string(),
-                msg: to_json_binary(&execute_msg)?,
-                funds: vec![],
-            };
+/// Rejects recipients that could never receive the mint on XION: anything
+/// but a lowercase bech32 address with the configured prefix and a 20-byte
+/// (key) or 32-byte (contract or meta-account) payload.
+pub fn validate_xion_recipient(config: &Config, recipient: &str) -> Result<(), ContractError> {
+    let invalid = || ContractError::InvalidRecipient {
+        recipient: recipient.to_string(),
+        hrp: config.xion_hrp.clone(),
+    };
+    // XION's address validation only accepts the lowercase form
+    if recipient != recipient.to_lowercase() {
+        return Err(invalid());
+    }
+    let (hrp, data) = bech32::decode(recipient).map_err(|_| invalid())?;
+    if hrp.as_str() != config.xion_hrp || !matches!(data.len(), 20 | 32) {
+        return Err(invalid());
+    }
+    Ok(())
+}
 
-            Ok(Response::new()
-                .add_message(note_msg)
-                .add_attribute("action", "lock_and_mint")
-                .add_attribute("locked_token", info.sender)
-                .add_attribute("from_user", cw20_msg.sender)
-                .add_attribute("amount_locked", cw20_msg.amount)
-                .add_attribute("xion_recipient", xion_meta_account))
+fn validate_window(config: &Config) -> Result<(), ContractError> {
+    if let (Some(start), Some(end)) = (config.start_time, config.end_time) {
+        if start >= end {
+            return Err(ContractError::InvalidConfig {
+                reason: "start_time must be before end_time".to_string(),
+            });
         }
     }
+    Ok(())
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetConfig {} => {
-            let config = CONFIG.load(deps.storage)?;
-            to_json_binary(&ConfigResponse {
-                note_contract: config.note_contract.into_string(),
-                token_a: config.token_a.into_string(),
-                token_b: config.token_b.into_string(),
-                xion_mint_contract: config.xion_mint_contract,
-            })
-        }
+fn validate_fee(config: &Config) -> Result<(), ContractError> {
+    if config.fee_bps > MAX_FEE_BPS {
+        return Err(ContractError::InvalidFee {
+            max_bps: MAX_FEE_BPS,
+        });
+    }
+    if config.fee_bps > 0 && config.treasury.is_none() {
+        return Err(ContractError::InvalidConfig {
+            reason: "a treasury is required to charge a fee".to_string(),
+        });
     }
+    Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::{to_binary, Empty, Uint128};
-    use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
-    use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor, IntoAddr};
+fn current_phase(deps: Deps, config: &Config, now: Timestamp) -> StdResult<MergerPhase> {
+    if FINALIZATION.may_load(deps.storage)?.is_some() {
+        return Ok(MergerPhase::Finalized);
+    }
+    Ok(match (config.start_time, config.end_time) {
+        (Some(start), _) if now < start => MergerPhase::Pending,
+        (_, Some(end)) if now >= end => MergerPhase::Closed,
+        _ if !fully_approved(deps)? => MergerPhase::AwaitingApproval,
+        _ => MergerPhase::Open,
+    })
+}
 
-    use crate::ContractError;
+fn fully_approved(deps: Deps) -> StdResult<bool> {
+    for entry in APPROVERS.range(deps.storage, None, None, Order::Ascending) {
+        if entry?.1.is_none() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
 
-    #[cfg(not(feature = "library"))]
-    use cosmwasm_std::entry_point;
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    update: UpdateConfigMsg,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
 
-    use crate::msg::PolytoneExecuteMsg as MockNoteMsg;
+    let mut config = CONFIG.load(deps.storage)?;
+    let mut response = Response::new().add_attribute("action", "update_config");
 
-    #[cfg_attr(not(feature = "library"), entry_point)]
-    pub fn mock_note_instantiate(
-        _deps: cosmwasm_std::DepsMut,
-        _env: cosmwasm_std::Env,
-        _info: cosmwasm_std::MessageInfo,
-        _msg: Empty,
-    ) -> Result<cosmwasm_std::Response, cosmwasm_std::StdError> {
-        Ok(cosmwasm_std::Response::new().add_attribute("mock_note", "init"))
+    for field in &update.clear {
+        let also_set = match field {
+            OptionalConfigField::Guardian => update.guardian.is_some(),
+            OptionalConfigField::XionProxy => update.xion_proxy.is_some(),
+            OptionalConfigField::Treasury => update.treasury.is_some(),
+            OptionalConfigField::StartTime => update.start_time.is_some(),
+            OptionalConfigField::EndTime => update.end_time.is_some(),
+        };
+        if also_set {
+            return Err(ContractError::InvalidConfig {
+                reason: format!("{} can't be both set and cleared", field.as_str()),
+            });
+        }
+        match field {
+            OptionalConfigField::Guardian => config.guardian = None,
+            OptionalConfigField::XionProxy => config.xion_proxy = None,
+            OptionalConfigField::Treasury => config.treasury = None,
+            OptionalConfigField::StartTime => config.start_time = None,
+            OptionalConfigField::EndTime => config.end_time = None,
+        }
+        response = response.add_attribute("cleared", field.as_str());
     }
-
-    #[cfg_attr(not(feature = "library"), entry_point)]
-    pub fn mock_note_execute(
-        _deps: cosmwasm_std::DepsMut,
-        _env: cosmwasm_std::Env,
-        info: cosmwasm_std::MessageInfo,
-        msg: MockNoteMsg,
-    ) -> Result<cosmwasm_std::Response, cosmwasm_std::StdError> {
-        match msg {
-            MockNoteMsg::Execute {
-                msgs,
-                callback,
-                timeout_seconds,
-            } => {
-                Ok(cosmwasm_std::Response::new()
-                    .add_attribute("mock_note", "received_execute")
-                    .add_attribute("caller", info.sender.to_string())
-                    .add_attribute("msgs_len", msgs.len().to_string())
-                    .add_attribute("timeout_seconds", timeout_seconds.to_string())
-                    .add_attribute("callback", format!("{:?}", callback)))
-            }
+    if let Some(guardian) = update.guardian {
+        config.guardian = Some(deps.api.addr_validate(&guardian)?);
+        response = response.add_attribute("guardian", guardian);
+    }
+    if let Some(note_contract) = update.note_contract {
+        // callbacks for locks in flight can only come from the old note
+        let pending = locks()
+            .idx
+            .status
+            .prefix(LockStatus::Pending.as_str().to_string())
+            .range(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some();
+        if pending {
+            return Err(ContractError::InvalidConfig {
+                reason: "note_contract can't change while locks are pending".to_string(),
+            });
         }
+        config.note_contract = deps.api.addr_validate(&note_contract)?;
+        response = response.add_attribute("note_contract", note_contract);
     }
-
-    #[cfg_attr(not(feature = "library"), entry_point)]
-    pub fn mock_note_query(
-        _deps: cosmwasm_std::Deps<cosmwasm_std::Empty>,
-        _env: cosmwasm_std::Env,
-        _msg: cosmwasm_std::Binary,
-    ) -> Result<cosmwasm_std::Binary, cosmwasm_std::StdError> {
-        Ok(to_binary("no queries")?)
+    if let Some(xion_mint_contract) = update.xion_mint_contract {
+        config.xion_mint_contract = validate_xion_contract(xion_mint_contract)?;
+        response = response.add_attribute("xion_mint_contract", &config.xion_mint_contract);
     }
-
-    fn mock_note_contract() -> Box<dyn Contract<Empty>> {
-        let contract =
-            ContractWrapper::new(mock_note_execute, mock_note_instantiate, mock_note_query);
-        Box::new(contract)
+    if let Some(xion_proxy) = update.xion_proxy {
+        config.xion_proxy = Some(deps.api.addr_validate(&xion_proxy)?);
+        response = response.add_attribute("xion_proxy", xion_proxy);
     }
-
-    fn merger_contract() -> Box<dyn Contract<Empty>> {
-        let contract = ContractWrapper::new(execute, instantiate, query);
-        Box::new(contract)
+    if let Some(timeout_seconds) = update.timeout_seconds {
+        config.timeout_seconds = validate_timeout(timeout_seconds)?;
+        response = response.add_attribute("timeout_seconds", timeout_seconds.to_string());
     }
-
-    fn cw20_base_contract() -> Box<dyn Contract<Empty>> {
-        let contract = ContractWrapper::new(
-            cw20_base::contract::execute,
-            cw20_base::contract::instantiate,
-            cw20_base::contract::query,
-        );
-        Box::new(contract)
+    if let Some(xion_hrp) = update.xion_hrp {
+        config.xion_hrp = validate_hrp(&xion_hrp)?;
+        response = response.add_attribute("xion_hrp", xion_hrp);
+    }
+    let window_cleared = update.clear.iter().any(|field| {
+        matches!(
+            field,
+            OptionalConfigField::StartTime | OptionalConfigField::EndTime
+        )
+    });
+    if update.start_time.is_some() || update.end_time.is_some() || window_cleared {
+        if FINALIZATION.may_load(deps.storage)?.is_some() {
+            return Err(ContractError::MergerFinalized {});
+        }
+        if let Some(start_time) = update.start_time {
+            config.start_time = Some(start_time);
+            response = response.add_attribute("start_time", start_time.to_string());
+        }
+        if let Some(end_time) = update.end_time {
+            config.end_time = Some(end_time);
+            response = response.add_attribute("end_time", end_time.to_string());
+        }
+        validate_window(&config)?;
+    }
+    if let Some(treasury) = update.treasury {
+        config.treasury = Some(deps.api.addr_validate(&treasury)?);
+        response = response.add_attribute("treasury", treasury);
+    }
+    if let Some(fee_bps) = update.fee_bps {
+        config.fee_bps = fee_bps;
+        response = response.add_attribute("fee_bps", fee_bps.to_string());
+    }
+    validate_fee(&config)?;
+    if let Some(batching) = update.batching {
+        config.batching = batching;
+        response = response.add_attribute("batching", batching.to_string());
     }
 
-    fn setup() -> (
-        App,
-        Addr, 
-        Addr, 
-        Addr, 
-        Addr, 
-    ) {
-        let mut app = App::default();
+    CONFIG.save(deps.storage, &config)?;
+    Ok(response)
+}
 
-        let cw20_code_id = app.store_code(cw20_base_contract());
-        let merger_code_id = app.store_code(merger_contract());
-        let note_code_id = app.store_code(mock_note_contract());
+pub fn set_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    paused: bool,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.guardian.as_ref() != Some(&info.sender) {
+        assert_owner(deps.as_ref(), &info)?;
+    }
 
-        // instantiate the mock note
-        let note_addr = app
-            .instantiate_contract(
-                note_code_id,
-                "note_deployer".into_addr(),
-                &Empty {}, 
-                &[],
-                "Mock Note",
-                None,
-            )
-            .unwrap();
+    PAUSE.save(
+        deps.storage,
+        &PauseState {
+            paused,
+            reason: reason.clone(),
+        },
+    )?;
 
-        // instantiate Token A
-        let token_a_admin = "token_a_admin".into_addr();
-        let token_a_init = cw20_base::msg::InstantiateMsg {
-            name: "Token A".into(),
-            symbol: "TKNA".into(),
-            decimals: 6,
-            initial_balances: vec![],
-            mint: Some(cw20::MinterResponse {
-                minter: token_a_admin.to_string(),
-                cap: None,
-            }),
-            marketing: None,
-        };
-        let token_a_addr = app
-            .instantiate_contract(
-                cw20_code_id,
-                token_a_admin.clone(),
-                &token_a_init,
-                &[],
-                "Token A",
-                None,
-            )
-            .unwrap();
+    let action = if paused { "pause" } else { "unpause" };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("sender", info.sender)
+        .add_attribute("reason", reason.unwrap_or_default()))
+}
 
-        let xion_mint_addr = token_a_addr.clone();
+pub fn transfer_ownership(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
 
-        // instantiate the Merger
-        let placeholder = "placeholder".into_addr();
-        let init_msg = InstantiateMsg {
-            note_contract: note_addr.to_string(),
-            token_a: token_a_addr.to_string(),
-            token_b: placeholder.to_string(),
-            xion_mint_contract: xion_mint_addr.to_string(),
-        };
-        let merger_addr = app
-            .instantiate_contract(
-                merger_code_id,
-                "merger_deployer".into_addr(),
-                &init_msg,
-                &[],
-                "Merger Contract",
-                None,
-            )
-            .unwrap();
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    OWNERSHIP.update(deps.storage, |mut ownership| -> StdResult<_> {
+        ownership.pending_owner = Some(new_owner.clone());
+        Ok(ownership)
+    })?;
 
-        (app, merger_addr, token_a_addr, note_addr, token_a_admin)
+    Ok(Response::new()
+        .add_attribute("action", "transfer_ownership")
+        .add_attribute("pending_owner", new_owner))
+}
+
+pub fn accept_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut ownership = OWNERSHIP.load(deps.storage)?;
+    if ownership.pending_owner.as_ref() != Some(&info.sender) {
+        return Err(ContractError::NotPendingOwner {});
     }
 
-    #[test]
-    fn test_init() {
-        let (app, merger_addr, token_a_addr, note_addr, _) = setup();
+    let previous_owner = ownership.owner;
+    ownership.owner = info.sender;
+    ownership.pending_owner = None;
+    OWNERSHIP.save(deps.storage, &ownership)?;
 
-        let cfg: super::Config = app
-            .wrap()
-            .query_wasm_smart(merger_addr, &QueryMsg::GetConfig {})
-            .unwrap();
+    Ok(Response::new()
+        .add_attribute("action", "accept_ownership")
+        .add_attribute("previous_owner", previous_owner)
+        .add_attribute("owner", ownership.owner))
+}
 
-        assert_eq!(cfg.token_a, token_a_addr);
-        assert_eq!(cfg.note_contract, note_addr);
+/// Validates and registers a source token, returning its registry key:
+/// the contract address for CW20s or the denom for native tokens.
+fn add_source_token(deps: DepsMut, source: SourceTokenMsg) -> Result<String, ContractError> {
+    let token = match source.kind {
+        TokenKind::Cw20 => deps.api.addr_validate(&source.token)?.into_string(),
+        TokenKind::Native => validate_denom(source.token)?,
+    };
+    if SOURCE_TOKENS.has(deps.storage, &token) {
+        return Err(ContractError::SourceTokenExists { token });
     }
 
-    #[test]
+    SOURCE_TOKENS.save(
+        deps.storage,
+        &token,
+        &SourceTokenConfig {
+            kind: source.kind,
+            ratio: validate_ratio(source.ratio)?,
+            cap: source.cap,
+            user_cap: source.user_cap,
+            mode: source.mode,
+            merkle_root: source.merkle_root.map(validate_merkle_root).transpose()?,
+            enabled: true,
+        },
+    )?;
+    Ok(token)
+}
+
+fn validate_merkle_root(root: String) -> Result<String, ContractError> {
+    let mut bytes: Hash = [0; 32];
+    if hex::decode_to_slice(&root, &mut bytes).is_err() {
+        return Err(ContractError::InvalidMerkleRoot { root });
+    }
+    Ok(root.to_lowercase())
+}
+
+/// Checks a denom against the Cosmos SDK denom format.
+fn validate_denom(denom: String) -> Result<String, ContractError> {
+    let valid = (3..=128).contains(&denom.len())
+        && denom.starts_with(|c: char| c.is_ascii_alphabetic())
+        && denom
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c));
+    if !valid {
+        return Err(ContractError::InvalidDenom { denom });
+    }
+    Ok(denom)
+}
+
+fn set_source_token_enabled(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: String,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
+
+    let mut source = SOURCE_TOKENS
+        .may_load(deps.storage, &token)?
+        .ok_or(ContractError::InvalidToken {})?;
+    source.enabled = enabled;
+    SOURCE_TOKENS.save(deps.storage, &token, &source)?;
+
+    let action = if enabled {
+        "enable_source_token"
+    } else {
+        "disable_source_token"
+    };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("source_token", token))
+}
+
+/// Lowering a cap below what is already locked only blocks new locks.
+fn set_source_token_caps(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: String,
+    cap: Option<Uint128>,
+    user_cap: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
+
+    let mut source = SOURCE_TOKENS
+        .may_load(deps.storage, &token)?
+        .ok_or(ContractError::InvalidToken {})?;
+    source.cap = cap;
+    source.user_cap = user_cap;
+    SOURCE_TOKENS.save(deps.storage, &token, &source)?;
+
+    let display = |cap: Option<Uint128>| cap.map(|cap| cap.to_string()).unwrap_or_default();
+    Ok(Response::new()
+        .add_attribute("action", "set_source_token_caps")
+        .add_attribute("source_token", token)
+        .add_attribute("cap", display(cap))
+        .add_attribute("user_cap", display(user_cap)))
+}
+
+/// Locks already made keep the mode they were made with.
+fn set_merge_mode(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: String,
+    mode: MergeMode,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
+
+    let mut source = SOURCE_TOKENS
+        .may_load(deps.storage, &token)?
+        .ok_or(ContractError::InvalidToken {})?;
+    source.mode = mode;
+    SOURCE_TOKENS.save(deps.storage, &token, &source)?;
+
+    let (mode, immediate) = match source.mode {
+        MergeMode::Lock => ("lock", false),
+        MergeMode::Burn { immediate } => ("burn", immediate),
+    };
+    Ok(Response::new()
+        .add_attribute("action", "set_merge_mode")
+        .add_attribute("source_token", token)
+        .add_attribute("mode", mode)
+        .add_attribute("immediate", immediate.to_string()))
+}
+
+/// Locked amounts already count against a new root's allowances.
+fn set_merkle_root(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: String,
+    root: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
+
+    let mut source = SOURCE_TOKENS
+        .may_load(deps.storage, &token)?
+        .ok_or(ContractError::InvalidToken {})?;
+    source.merkle_root = root.map(validate_merkle_root).transpose()?;
+    SOURCE_TOKENS.save(deps.storage, &token, &source)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_merkle_root")
+        .add_attribute("source_token", token)
+        .add_attribute("merkle_root", source.merkle_root.unwrap_or_default()))
+}
+
+/// Locks already made with a removed token can still be refunded, since
+/// each lock records the token it holds.
+fn remove_source_token(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: String,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
+
+    if !SOURCE_TOKENS.has(deps.storage, &token) {
+        return Err(ContractError::InvalidToken {});
+    }
+    SOURCE_TOKENS.remove(deps.storage, &token);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_source_token")
+        .add_attribute("source_token", token))
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let source = load_source_token(deps.as_ref(), info.sender.as_str(), TokenKind::Cw20)?;
+
+    let hook: ReceiveMsg = from_json(&cw20_msg.msg)?;
+
+    let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+    lock_and_mint(
+        deps,
+        env,
+        sender,
+        info.sender.into_string(),
+        source,
+        cw20_msg.amount,
+        hook,
+    )
+}
+
+/// Locks or deposits a native or IBC denom sent as the message funds.
+pub fn lock_native(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    hook: ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let coin = match info.funds.as_slice() {
+        [coin] if !coin.amount.is_zero() => coin.clone(),
+        _ => return Err(ContractError::InvalidFunds {}),
+    };
+    let source = load_source_token(deps.as_ref(), &coin.denom, TokenKind::Native)?;
+
+    lock_and_mint(
+        deps,
+        env,
+        info.sender,
+        coin.denom,
+        source,
+        coin.amount,
+        hook,
+    )
+}
+
+fn load_source_token(
+    deps: Deps,
+    token: &str,
+    kind: TokenKind,
+) -> Result<SourceTokenConfig, ContractError> {
+    let source = SOURCE_TOKENS
+        .may_load(deps.storage, token)?
+        .filter(|source| source.kind == kind)
+        .ok_or(ContractError::InvalidToken {})?;
+    if !source.enabled {
+        return Err(ContractError::SourceTokenDisabled {
+            token: token.to_string(),
+        });
+    }
+    Ok(source)
+}
+
+/// Records the lock, then either asks the note to mint the converted
+/// amount on XION or queues it: deposits in the sender's basket, other
+/// locks in the batch queue while batching is on.
+fn lock_and_mint(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    token: String,
+    source: SourceTokenConfig,
+    amount: Uint128,
+    hook: ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let (xion_meta_account, proof, deposit) = match hook {
+        ReceiveMsg::Lock {
+            xion_meta_account,
+            proof,
+        } => (xion_meta_account, proof, false),
+        ReceiveMsg::Deposit {
+            xion_meta_account,
+            proof,
+        } => (xion_meta_account, proof, true),
+    };
+    let queued = deposit || config.batching;
+
+    if PAUSE.may_load(deps.storage)?.unwrap_or_default().paused {
+        return Err(ContractError::Paused {});
+    }
+    let phase = current_phase(deps.as_ref(), &config, env.block.time)?;
+    if phase != MergerPhase::Open {
+        return Err(ContractError::MergerNotOpen {
+            phase: phase.as_str().to_string(),
+        });
+    }
+    validate_xion_recipient(&config, &xion_meta_account)?;
+
+    let fee = amount.multiply_ratio(config.fee_bps, 10_000u128);
+    let mint_amount = convert_amount(amount - fee, source.ratio)?;
+
+    let locked = CUMULATIVE_LOCKED
+        .may_load(deps.storage, &token)?
+        .unwrap_or_default();
+    let user_locked = USER_LOCKED
+        .may_load(deps.storage, (&sender, &token))?
+        .unwrap_or_default();
+    if let Some(remaining) = remaining_capacity(&source, locked, user_locked) {
+        if amount > remaining {
+            return Err(ContractError::CapExceeded { remaining });
+        }
+    }
+    if let Some(root) = &source.merkle_root {
+        let proof = proof.ok_or(ContractError::MissingProof {})?;
+        verify_eligibility(root, &sender, &proof)?;
+        let remaining = proof.max_amount.saturating_sub(user_locked);
+        if amount > remaining {
+            return Err(ContractError::EligibilityExceeded { remaining });
+        }
+    }
+    // an immediate burn leaves only the fee behind for the treasury
+    let burned = match source.mode {
+        MergeMode::Burn { immediate: true } => amount - fee,
+        _ => Uint128::zero(),
+    };
+    CUMULATIVE_LOCKED.save(deps.storage, &token, &locked.checked_add(amount)?)?;
+    TOTAL_LOCKED.update(deps.storage, &token, |held| -> StdResult<_> {
+        Ok(held.unwrap_or_default().checked_add(amount - burned)?)
+    })?;
+    USER_LOCKED.save(
+        deps.storage,
+        (&sender, &token),
+        &user_locked.checked_add(amount)?,
+    )?;
+
+    let lock_id = LOCK_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    LOCK_COUNT.save(deps.storage, &lock_id)?;
+    let lock = Lock {
+        sender: sender.clone(),
+        token: token.clone(),
+        kind: source.kind,
+        amount,
+        fee,
+        mode: source.mode,
+        mint_amount,
+        xion_recipient: xion_meta_account.clone(),
+        block_height: env.block.height,
+        status: if queued {
+            LockStatus::Queued
+        } else {
+            LockStatus::Pending
+        },
+    };
+    locks().save(deps.storage, lock_id, &lock)?;
+
+    let mut response = Response::new();
+    if !burned.is_zero() {
+        TOTAL_BURNED.update(deps.storage, &token, |total| -> StdResult<_> {
+            Ok(total.unwrap_or_default().checked_add(burned)?)
+        })?;
+        response = response
+            .add_message(burn_tokens_msg(&lock.kind, &token, burned)?)
+            .add_attribute("amount_burned", burned);
+    }
+    if deposit {
+        let mut basket = BASKETS.may_load(deps.storage, &sender)?.unwrap_or_default();
+        if basket.len() >= MAX_BASKET_SIZE as usize {
+            return Err(ContractError::BasketFull {
+                max: MAX_BASKET_SIZE,
+            });
+        }
+        basket.push(lock_id);
+        BASKETS.save(deps.storage, &sender, &basket)?;
+        response = response.add_attribute("action", "deposit");
+    } else if queued {
+        BATCH_QUEUE.push_back(deps.storage, &lock_id)?;
+        response = response.add_attribute("action", "queue_lock");
+    } else {
+        // the note executes the mint through our proxy on XION and reports
+        // back with the lock's details
+        let payload = MintCallback {
+            lock_id,
+            sender: sender.to_string(),
+            token: token.clone(),
+            amount,
+        };
+        let note_msg = note_execute_msg(
+            &config,
+            &env,
+            vec![mint_msg(&config, lock_id, &lock)?],
+            to_json_binary(&payload)?,
+        )?;
+        response = response
+            .add_message(note_msg)
+            .add_attribute("action", "lock_and_mint");
+    }
+
+    Ok(response
+        .add_attribute("lock_id", lock_id.to_string())
+        .add_attribute("locked_token", token)
+        .add_attribute("from_user", sender)
+        .add_attribute("amount_locked", amount)
+        .add_attribute("fee", fee)
+        .add_attribute("exchange_ratio", source.ratio.to_string())
+        .add_attribute("amount_minted", mint_amount)
+        .add_attribute("xion_recipient", xion_meta_account))
+}
+
+fn verify_eligibility(
+    root: &str,
+    sender: &Addr,
+    proof: &EligibilityProof,
+) -> Result<(), ContractError> {
+    let decode = |hash: &str| -> Result<Hash, ContractError> {
+        let mut bytes: Hash = [0; 32];
+        hex::decode_to_slice(hash, &mut bytes).map_err(|_| ContractError::InvalidProof {})?;
+        Ok(bytes)
+    };
+    let root = decode(root)?;
+    let siblings = proof
+        .proof
+        .iter()
+        .map(|hash| decode(hash))
+        .collect::<Result<Vec<_>, _>>()?;
+    let leaf = leaf_hash(sender.as_str(), proof.max_amount);
+    if !verify_proof(&root, leaf, &siblings) {
+        return Err(ContractError::InvalidProof {});
+    }
+    Ok(())
+}
+
+/// Sends every lock in the sender's basket to the note as one packet,
+/// with one mint per lock so each keeps its own nonce on XION.
+pub fn flush(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if PAUSE.may_load(deps.storage)?.unwrap_or_default().paused {
+        return Err(ContractError::Paused {});
+    }
+
+    let lock_ids = BASKETS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    if lock_ids.is_empty() {
+        return Err(ContractError::EmptyBasket {});
+    }
+    BASKETS.remove(deps.storage, &info.sender);
+
+    let mut msgs = Vec::with_capacity(lock_ids.len());
+    for &lock_id in &lock_ids {
+        let mut lock = locks().load(deps.storage, lock_id)?;
+        lock.status = LockStatus::Pending;
+        locks().save(deps.storage, lock_id, &lock)?;
+        msgs.push(mint_msg(&config, lock_id, &lock)?);
+    }
+
+    let payload = FlushCallback {
+        lock_ids: lock_ids.clone(),
+    };
+    let note_msg = note_execute_msg(&config, &env, msgs, to_json_binary(&payload)?)?;
+
+    Ok(Response::new()
+        .add_message(note_msg)
+        .add_attribute("action", "flush")
+        .add_attribute("from_user", info.sender)
+        .add_attribute("lock_ids", join_lock_ids(&lock_ids)))
+}
+
+/// Sends the oldest queued locks to XION as one `BatchMint`. Paused
+/// merges hold the queue.
+pub fn process_batch(
+    deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if PAUSE.may_load(deps.storage)?.unwrap_or_default().paused {
+        return Err(ContractError::Paused {});
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_BATCH_SIZE).clamp(1, MAX_BATCH_SIZE);
+    let mut lock_ids = vec![];
+    let mut entries = vec![];
+    while lock_ids.len() < limit as usize {
+        let Some(lock_id) = BATCH_QUEUE.pop_front(deps.storage)? else {
+            break;
+        };
+        let mut lock = locks().load(deps.storage, lock_id)?;
+        lock.status = LockStatus::Pending;
+        locks().save(deps.storage, lock_id, &lock)?;
+        entries.push(MintEntry {
+            amount: lock.mint_amount,
+            recipient: lock.xion_recipient,
+            nonce: lock_id,
+            source_token: Some(lock.token),
+        });
+        lock_ids.push(lock_id);
+    }
+    if lock_ids.is_empty() {
+        return Err(ContractError::EmptyBatch {});
+    }
+
+    let batch_mint = WasmMsg::Execute {
+        contract_addr: config.xion_mint_contract.clone(),
+        msg: to_json_binary(&XionMinterExecuteMsg::BatchMint { entries })?,
+        funds: vec![],
+    };
+    let payload = FlushCallback {
+        lock_ids: lock_ids.clone(),
+    };
+    let note_msg = note_execute_msg(
+        &config,
+        &env,
+        vec![batch_mint.into()],
+        to_json_binary(&payload)?,
+    )?;
+
+    Ok(Response::new()
+        .add_message(note_msg)
+        .add_attribute("action", "process_batch")
+        .add_attribute("count", lock_ids.len().to_string())
+        .add_attribute("remaining", BATCH_QUEUE.len(deps.storage)?.to_string())
+        .add_attribute("lock_ids", join_lock_ids(&lock_ids)))
+}
+
+fn join_lock_ids(lock_ids: &[u64]) -> String {
+    lock_ids
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// xion-minter `Mint` for a recorded lock.
+fn mint_msg(config: &Config, lock_id: u64, lock: &Lock) -> StdResult<CosmosMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: config.xion_mint_contract.clone(),
+        msg: to_json_binary(&XionMinterExecuteMsg::Mint {
+            amount: lock.mint_amount,
+            recipient: Some(lock.xion_recipient.clone()),
+            nonce: lock_id,
+            source_token: Some(lock.token.clone()),
+        })?,
+        funds: vec![],
+    }
+    .into())
+}
+
+/// Asks the note to run `msgs` on XION and call back with `callback`.
+fn note_execute_msg(
+    config: &Config,
+    env: &Env,
+    msgs: Vec<CosmosMsg>,
+    callback: Binary,
+) -> StdResult<WasmMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: config.note_contract.to_string(),
+        msg: to_json_binary(&PolytoneExecuteMsg::Execute {
+            msgs,
+            callback: Some(CallbackRequest {
+                receiver: env.contract.address.to_string(),
+                msg: callback,
+            }),
+            timeout_seconds: Uint64::new(config.timeout_seconds),
+        })?,
+        funds: vec![],
+    })
+}
+
+/// Records the sender's approval. Approvals can't be withdrawn.
+pub fn approve(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    match APPROVERS.may_load(deps.storage, &info.sender)? {
+        None => return Err(ContractError::NotApprover {}),
+        Some(Some(_)) => {
+            return Err(ContractError::AlreadyApproved {
+                approver: info.sender.into_string(),
+            })
+        }
+        Some(None) => {}
+    }
+    APPROVERS.save(deps.storage, &info.sender, &Some(env.block.time))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "approve")
+        .add_attribute("approver", info.sender)
+        .add_attribute("approved", fully_approved(deps.as_ref())?.to_string()))
+}
+
+/// Replaces the approver set while the merger is still waiting to open;
+/// once locks have been accepted the approvals they relied on are fixed.
+pub fn set_approvers(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    approvers: Vec<String>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    match current_phase(deps.as_ref(), &config, env.block.time)? {
+        MergerPhase::Pending | MergerPhase::AwaitingApproval => {}
+        phase => {
+            return Err(ContractError::ApproversFrozen {
+                phase: phase.as_str().to_string(),
+            })
+        }
+    }
+
+    let approvers = approvers
+        .iter()
+        .map(|approver| deps.api.addr_validate(approver))
+        .collect::<StdResult<Vec<_>>>()?;
+    let removed = APPROVERS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .filter(|approver| !matches!(approver, Ok(approver) if approvers.contains(approver)))
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut response = Response::new().add_attribute("action", "set_approvers");
+    for approver in removed {
+        APPROVERS.remove(deps.storage, &approver);
+        response = response.add_attribute("removed", approver);
+    }
+    for approver in approvers {
+        if !APPROVERS.has(deps.storage, &approver) {
+            APPROVERS.save(deps.storage, &approver, &None)?;
+            response = response.add_attribute("added", approver);
+        }
+    }
+
+    Ok(response.add_attribute("approved", fully_approved(deps.as_ref())?.to_string()))
+}
+
+/// Permanently closes the merger once its window has ended, recording
+/// the lock count and emitting what each source token ended up with.
+/// Pending mints still settle and releases still go through afterwards.
+pub fn finalize(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    match current_phase(deps.as_ref(), &config, env.block.time)? {
+        MergerPhase::Closed => {}
+        MergerPhase::Finalized => return Err(ContractError::MergerFinalized {}),
+        _ => return Err(ContractError::MergerNotClosed {}),
+    }
+
+    let lock_count = LOCK_COUNT.may_load(deps.storage)?.unwrap_or_default();
+    FINALIZATION.save(
+        deps.storage,
+        &Finalization {
+            time: env.block.time,
+            lock_count,
+        },
+    )?;
+
+    let mut response = Response::new()
+        .add_attribute("action", "finalize")
+        .add_attribute("lock_count", lock_count.to_string());
+    for entry in TOTAL_LOCKED.range(deps.storage, None, None, Order::Ascending) {
+        let (token, amount) = entry?;
+        response = response.add_attribute("total_locked", format!("{}:{}", token, amount));
+    }
+    Ok(response)
+}
+
+/// The most `user` may still lock of a source token under both its global
+/// and per-address caps, or `None` if neither is set.
+fn remaining_capacity(
+    source: &SourceTokenConfig,
+    cumulative_locked: Uint128,
+    user_locked: Uint128,
+) -> Option<Uint128> {
+    let global = source.cap.map(|cap| cap.saturating_sub(cumulative_locked));
+    let user = source.user_cap.map(|cap| cap.saturating_sub(user_locked));
+    match (global, user) {
+        (Some(global), Some(user)) => Some(global.min(user)),
+        (global, user) => global.or(user),
+    }
+}
+
+/// Rejects ratios that would mint nothing.
+fn validate_ratio(ratio: Decimal) -> Result<Decimal, ContractError> {
+    if ratio.is_zero() {
+        return Err(ContractError::InvalidRatio {});
+    }
+    Ok(ratio)
+}
+
+/// Converts a locked amount into the merged token amount, rounding down
+/// so the merger never mints more than the ratio allows.
+pub fn convert_amount(amount: Uint128, ratio: Decimal) -> Result<Uint128, ContractError> {
+    let converted = amount.checked_mul_floor(ratio)?;
+    if converted.is_zero() {
+        return Err(ContractError::InvalidAmount {});
+    }
+    Ok(converted)
+}
+
+/// Records the outcome of a lock's mint on XION and refunds the locked
+/// tokens if the mint failed or timed out. The callback message is the
+/// `MintCallback` we attached when the lock was sent to the note.
+pub fn handle_callback(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    callback: CallbackMessage,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.note_contract || callback.initiator != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // polytone reports a timed out execute packet as `Execute(Err("timeout"))`.
+    // A packet's msgs execute atomically, so one status covers every lock.
+    let status = match callback.result {
+        Callback::Execute(Ok(_)) => LockStatus::Minted,
+        Callback::Execute(Err(err)) if err == "timeout" => LockStatus::TimedOut,
+        Callback::Execute(Err(_)) | Callback::Query(_) | Callback::FatalError(_) => {
+            LockStatus::Failed
+        }
+    };
+
+    let mut response = Response::new()
+        .add_attribute("action", "mint_callback")
+        .add_attribute("status", status.as_str());
+    for lock_id in parse_callback_lock_ids(&callback.initiator_msg)? {
+        response = settle_lock(deps.branch(), &config, lock_id, status.clone(), response)?;
+    }
+
+    Ok(response)
+}
+
+/// Records the outcome of a lock's mint, paying out its fee if it minted
+/// and refunding it otherwise.
+fn settle_lock(
+    deps: DepsMut,
+    config: &Config,
+    lock_id: u64,
+    status: LockStatus,
+    mut response: Response,
+) -> Result<Response, ContractError> {
+    let mut lock = locks()
+        .may_load(deps.storage, lock_id)?
+        .ok_or(ContractError::LockNotFound { lock_id })?;
+
+    if lock.status != LockStatus::Pending {
+        return Err(ContractError::LockAlreadySettled { lock_id });
+    }
+    lock.status = status;
+    locks().save(deps.storage, lock_id, &lock)?;
+    response = response.add_attribute("lock_id", lock_id.to_string());
+
+    // the merged tokens now exist on XION and can be redeemed for these,
+    // unless they're about to be burned
+    if lock.status == LockStatus::Minted && lock.mode == MergeMode::Lock {
+        RELEASABLE.update(deps.storage, &lock.token, |releasable| -> StdResult<_> {
+            Ok(releasable
+                .unwrap_or_default()
+                .checked_add(lock.mint_amount)?)
+        })?;
+    }
+
+    // the mint went through, so the fee is earned
+    if lock.status == LockStatus::Minted && !lock.fee.is_zero() {
+        if let Some(treasury) = &config.treasury {
+            TOTAL_LOCKED.update(deps.storage, &lock.token, |locked| -> StdResult<_> {
+                Ok(locked.unwrap_or_default().checked_sub(lock.fee)?)
+            })?;
+            FEES_COLLECTED.update(deps.storage, &lock.token, |fees| -> StdResult<_> {
+                Ok(fees.unwrap_or_default().checked_add(lock.fee)?)
+            })?;
+            let fee_msg = send_tokens_msg(&lock.kind, &lock.token, treasury, lock.fee)?;
+            response = response
+                .add_message(fee_msg)
+                .add_attribute("treasury", treasury)
+                .add_attribute("fee", lock.fee);
+        }
+    }
+
+    // burn mode tokens are burned once their mint is confirmed
+    if lock.status == LockStatus::Minted && lock.mode == (MergeMode::Burn { immediate: false }) {
+        let burned = lock.amount - lock.fee;
+        TOTAL_LOCKED.update(deps.storage, &lock.token, |locked| -> StdResult<_> {
+            Ok(locked.unwrap_or_default().checked_sub(burned)?)
+        })?;
+        TOTAL_BURNED.update(deps.storage, &lock.token, |total| -> StdResult<_> {
+            Ok(total.unwrap_or_default().checked_add(burned)?)
+        })?;
+        response = response
+            .add_message(burn_tokens_msg(&lock.kind, &lock.token, burned)?)
+            .add_attribute("amount_burned", burned);
+    }
+
+    // nothing was minted on XION, so hand back whatever we still hold:
+    // only the fee if the rest was burned on receipt
+    if lock.status != LockStatus::Minted {
+        let refund_amount = match lock.mode {
+            MergeMode::Burn { immediate: true } => lock.fee,
+            _ => lock.amount,
+        };
+        TOTAL_LOCKED.update(deps.storage, &lock.token, |locked| -> StdResult<_> {
+            Ok(locked.unwrap_or_default().checked_sub(refund_amount)?)
+        })?;
+        CUMULATIVE_LOCKED.update(deps.storage, &lock.token, |locked| -> StdResult<_> {
+            Ok(locked.unwrap_or_default().checked_sub(refund_amount)?)
+        })?;
+
+        // locks made before per-address tracking aren't counted here
+        let key = (&lock.sender, lock.token.as_str());
+        let user_locked = USER_LOCKED.may_load(deps.storage, key)?.unwrap_or_default();
+        USER_LOCKED.save(
+            deps.storage,
+            key,
+            &user_locked.saturating_sub(refund_amount),
+        )?;
+        if !refund_amount.is_zero() {
+            let refund = send_tokens_msg(&lock.kind, &lock.token, &lock.sender, refund_amount)?;
+            response = response.add_message(refund);
+        }
+        response = response
+            .add_attribute("refund_recipient", &lock.sender)
+            .add_attribute("refund_amount", refund_amount);
+    }
+
+    Ok(response)
+}
+
+/// Lock ids a callback settles: one for a `MintCallback`, several for a
+/// `FlushCallback`.
+fn parse_callback_lock_ids(msg: &Binary) -> Result<Vec<u64>, ContractError> {
+    if let Ok(payload) = from_json::<MintCallback>(msg) {
+        return Ok(vec![payload.lock_id]);
+    }
+    if let Ok(payload) = from_json::<FlushCallback>(msg) {
+        return Ok(payload.lock_ids);
+    }
+    Err(ContractError::InvalidCallback {})
+}
+
+/// Releases locked source tokens for merged tokens burned on XION. The
+/// merged amount is converted back at the token's ratio, rounding down.
+/// Only as many merged tokens as were minted against `source_token` can
+/// be released for it, so nobody can drain another token's holders by
+/// redeeming at a more favourable ratio.
+pub fn release(
+    deps: DepsMut,
+    info: MessageInfo,
+    recipient: String,
+    source_token: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.xion_proxy.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if PAUSE.may_load(deps.storage)?.unwrap_or_default().paused {
+        return Err(ContractError::Paused {});
+    }
+
+    // disabled tokens can still be released, only removed ones cannot
+    let source = SOURCE_TOKENS
+        .may_load(deps.storage, &source_token)?
+        .ok_or(ContractError::InvalidToken {})?;
+    if source.mode != MergeMode::Lock {
+        return Err(ContractError::NotReleasable {
+            token: source_token,
+        });
+    }
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    let release_amount = amount.checked_div_floor(source.ratio)?;
+    if release_amount.is_zero() {
+        return Err(ContractError::InvalidAmount {});
+    }
+
+    let releasable = RELEASABLE
+        .may_load(deps.storage, &source_token)?
+        .unwrap_or_default();
+    if amount > releasable {
+        return Err(ContractError::InsufficientReleasable {
+            available: releasable,
+        });
+    }
+    RELEASABLE.save(deps.storage, &source_token, &(releasable - amount))?;
+    TOTAL_LOCKED.update(deps.storage, &source_token, |locked| -> StdResult<_> {
+        Ok(locked.unwrap_or_default().checked_sub(release_amount)?)
+    })?;
+
+    Ok(Response::new()
+        .add_message(send_tokens_msg(
+            &source.kind,
+            &source_token,
+            &recipient,
+            release_amount,
+        )?)
+        .add_attribute("action", "release")
+        .add_attribute("source_token", source_token)
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount_burned", amount)
+        .add_attribute("amount_released", release_amount))
+}
+
+fn send_tokens_msg(
+    kind: &TokenKind,
+    token: &str,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    Ok(match kind {
+        TokenKind::Cw20 => WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+        TokenKind::Native => BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount.u128(), token),
+        }
+        .into(),
+    })
+}
+
+fn burn_tokens_msg(kind: &TokenKind, token: &str, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(match kind {
+        TokenKind::Cw20 => WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Burn { amount })?,
+            funds: vec![],
+        }
+        .into(),
+        TokenKind::Native => BankMsg::Burn {
+            amount: coins(amount.u128(), token),
+        }
+        .into(),
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetConfig {} => {
+            let config = CONFIG.load(deps.storage)?;
+            to_json_binary(&ConfigResponse {
+                guardian: config.guardian.map(Addr::into_string),
+                note_contract: config.note_contract.into_string(),
+                xion_mint_contract: config.xion_mint_contract,
+                xion_proxy: config.xion_proxy.map(Addr::into_string),
+                timeout_seconds: config.timeout_seconds,
+                xion_hrp: config.xion_hrp,
+                start_time: config.start_time,
+                end_time: config.end_time,
+                fee_bps: config.fee_bps,
+                treasury: config.treasury.map(Addr::into_string),
+                batching: config.batching,
+            })
+        }
+        QueryMsg::GetPhase {} => to_json_binary(&query_phase(deps, env)?),
+        QueryMsg::GetApprovals {} => to_json_binary(&query_approvals(deps)?),
+        QueryMsg::GetOwnership {} => {
+            let ownership = OWNERSHIP.load(deps.storage)?;
+            to_json_binary(&OwnershipResponse {
+                owner: ownership.owner.into_string(),
+                pending_owner: ownership.pending_owner.map(Addr::into_string),
+            })
+        }
+        QueryMsg::GetPauseStatus {} => {
+            let pause = PAUSE.may_load(deps.storage)?.unwrap_or_default();
+            to_json_binary(&PauseResponse {
+                paused: pause.paused,
+                reason: pause.reason,
+            })
+        }
+        QueryMsg::GetSourceToken { token } => {
+            let source = SOURCE_TOKENS.load(deps.storage, &token)?;
+            to_json_binary(&source_token_response(deps, token, source)?)
+        }
+        QueryMsg::GetSourceTokens { start_after, limit } => {
+            to_json_binary(&query_source_tokens(deps, start_after, limit)?)
+        }
+        QueryMsg::GetUserCapacity { user, token } => {
+            to_json_binary(&query_user_capacity(deps, user, token)?)
+        }
+        QueryMsg::GetFeesCollected { start_after, limit } => {
+            to_json_binary(&query_fees_collected(deps, start_after, limit)?)
+        }
+        QueryMsg::GetLock { lock_id } => {
+            let lock = locks().load(deps.storage, lock_id)?;
+            to_json_binary(&lock_response(lock_id, lock))
+        }
+        QueryMsg::GetLocksByUser {
+            user,
+            start_after,
+            limit,
+        } => to_json_binary(&query_locks_by_user(deps, user, start_after, limit)?),
+        QueryMsg::GetLocksByToken {
+            token,
+            start_after,
+            limit,
+        } => to_json_binary(&query_locks_by_token(deps, token, start_after, limit)?),
+        QueryMsg::GetBasket { user } => to_json_binary(&query_basket(deps, user)?),
+        QueryMsg::GetBatchQueue {} => to_json_binary(&BatchQueueResponse {
+            queued: BATCH_QUEUE.len(deps.storage)?,
+        }),
+        QueryMsg::GetLocksByStatus {
+            status,
+            start_after,
+            limit,
+        } => to_json_binary(&query_locks_by_status(deps, status, start_after, limit)?),
+    }
+}
+
+fn query_phase(deps: Deps, env: Env) -> StdResult<PhaseResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let now = env.block.time;
+    let phase = current_phase(deps, &config, now)?;
+    let seconds_until = |time: Option<Timestamp>| {
+        time.filter(|time| *time > now)
+            .map(|time| time.seconds() - now.seconds())
+    };
+    let (seconds_until_start, seconds_until_end) = match phase {
+        MergerPhase::Pending | MergerPhase::AwaitingApproval | MergerPhase::Open => (
+            seconds_until(config.start_time),
+            seconds_until(config.end_time),
+        ),
+        MergerPhase::Closed | MergerPhase::Finalized => (None, None),
+    };
+    Ok(PhaseResponse {
+        phase,
+        start_time: config.start_time,
+        end_time: config.end_time,
+        seconds_until_start,
+        seconds_until_end,
+        finalized_at: FINALIZATION
+            .may_load(deps.storage)?
+            .map(|finalization| finalization.time),
+    })
+}
+
+fn query_approvals(deps: Deps) -> StdResult<ApprovalsResponse> {
+    let approvals = APPROVERS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            item.map(|(approver, approved_at)| Approval {
+                approver: approver.into_string(),
+                approved_at,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(ApprovalsResponse {
+        approved: approvals
+            .iter()
+            .all(|approval| approval.approved_at.is_some()),
+        approvals,
+    })
+}
+
+fn source_token_response(
+    deps: Deps,
+    token: String,
+    source: SourceTokenConfig,
+) -> StdResult<SourceTokenResponse> {
+    let total_locked = TOTAL_LOCKED
+        .may_load(deps.storage, &token)?
+        .unwrap_or_default();
+    let cumulative_locked = CUMULATIVE_LOCKED
+        .may_load(deps.storage, &token)?
+        .unwrap_or_default();
+    let releasable = RELEASABLE
+        .may_load(deps.storage, &token)?
+        .unwrap_or_default();
+    Ok(SourceTokenResponse {
+        kind: source.kind,
+        ratio: source.ratio,
+        cap: source.cap,
+        user_cap: source.user_cap,
+        mode: source.mode,
+        merkle_root: source.merkle_root,
+        enabled: source.enabled,
+        total_locked,
+        cumulative_locked,
+        releasable,
+        total_burned: TOTAL_BURNED
+            .may_load(deps.storage, &token)?
+            .unwrap_or_default(),
+        remaining: source.cap.map(|cap| cap.saturating_sub(cumulative_locked)),
+        fees_collected: FEES_COLLECTED
+            .may_load(deps.storage, &token)?
+            .unwrap_or_default(),
+        token,
+    })
+}
+
+fn query_user_capacity(deps: Deps, user: String, token: String) -> StdResult<UserCapacityResponse> {
+    let user = deps.api.addr_validate(&user)?;
+    let source = SOURCE_TOKENS.load(deps.storage, &token)?;
+    let cumulative_locked = CUMULATIVE_LOCKED
+        .may_load(deps.storage, &token)?
+        .unwrap_or_default();
+    let locked = USER_LOCKED
+        .may_load(deps.storage, (&user, &token))?
+        .unwrap_or_default();
+    Ok(UserCapacityResponse {
+        remaining: remaining_capacity(&source, cumulative_locked, locked),
+        user: user.into_string(),
+        token,
+        locked,
+    })
+}
+
+fn query_source_tokens(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<SourceTokensResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let source_tokens = SOURCE_TOKENS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.and_then(|(token, source)| source_token_response(deps, token, source)))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(SourceTokensResponse { source_tokens })
+}
+
+/// Covers tokens since removed from the registry too.
+fn query_fees_collected(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<FeesCollectedResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let fees = FEES_COLLECTED
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(token, amount)| FeeCollected { token, amount }))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(FeesCollectedResponse { fees })
+}
+
+fn lock_response(lock_id: u64, lock: Lock) -> LockResponse {
+    LockResponse {
+        lock_id,
+        sender: lock.sender.into_string(),
+        token: lock.token,
+        amount: lock.amount,
+        fee: lock.fee,
+        mode: lock.mode,
+        mint_amount: lock.mint_amount,
+        xion_recipient: lock.xion_recipient,
+        block_height: lock.block_height,
+        status: lock.status,
+    }
+}
+
+fn collect_locks(
+    iter: Box<dyn Iterator<Item = StdResult<(u64, Lock)>> + '_>,
+    limit: Option<u32>,
+) -> StdResult<LocksResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let locks = iter
+        .take(limit)
+        .map(|item| item.map(|(lock_id, lock)| lock_response(lock_id, lock)))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(LocksResponse { locks })
+}
+
+fn query_basket(deps: Deps, user: String) -> StdResult<LocksResponse> {
+    let user = deps.api.addr_validate(&user)?;
+    let locks = BASKETS
+        .may_load(deps.storage, &user)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|lock_id| Ok(lock_response(lock_id, locks().load(deps.storage, lock_id)?)))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(LocksResponse { locks })
+}
+
+fn query_locks_by_user(
+    deps: Deps,
+    user: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<LocksResponse> {
+    let user = deps.api.addr_validate(&user)?;
+    let start = start_after.map(Bound::exclusive);
+    collect_locks(
+        locks()
+            .idx
+            .sender
+            .prefix(user)
+            .range(deps.storage, start, None, Order::Ascending),
+        limit,
+    )
+}
+
+fn query_locks_by_token(
+    deps: Deps,
+    token: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<LocksResponse> {
+    let start = start_after.map(Bound::exclusive);
+    collect_locks(
+        locks()
+            .idx
+            .token
+            .prefix(token)
+            .range(deps.storage, start, None, Order::Ascending),
+        limit,
+    )
+}
+
+fn query_locks_by_status(
+    deps: Deps,
+    status: LockStatus,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<LocksResponse> {
+    let start = start_after.map(Bound::exclusive);
+    collect_locks(
+        locks()
+            .idx
+            .status
+            .prefix(status.as_str().to_string())
+            .range(deps.storage, start, None, Order::Ascending),
+        limit,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{Empty, Uint128};
+    use cw20::Cw20ExecuteMsg;
+    use cw_multi_test::{App, Contract, ContractWrapper, Executor, IntoAddr};
+
+    use crate::ContractError;
+
+    const XION_RECIPIENT: &str = "xion1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5atkush";
+
+    #[cfg(not(feature = "library"))]
+    use cosmwasm_std::entry_point;
+
+    use crate::msg::PolytoneExecuteMsg as MockNoteMsg;
+
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn mock_note_instantiate(
+        _deps: cosmwasm_std::DepsMut,
+        _env: cosmwasm_std::Env,
+        _info: cosmwasm_std::MessageInfo,
+        _msg: Empty,
+    ) -> Result<cosmwasm_std::Response, cosmwasm_std::StdError> {
+        Ok(cosmwasm_std::Response::new().add_attribute("mock_note", "init"))
+    }
+
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn mock_note_execute(
+        _deps: cosmwasm_std::DepsMut,
+        _env: cosmwasm_std::Env,
+        info: cosmwasm_std::MessageInfo,
+        msg: MockNoteMsg,
+    ) -> Result<cosmwasm_std::Response, cosmwasm_std::StdError> {
+        match msg {
+            MockNoteMsg::Execute {
+                msgs,
+                callback,
+                timeout_seconds,
+            } => {
+                Ok(cosmwasm_std::Response::new()
+                    .add_attribute("mock_note", "received_execute")
+                    .add_attribute("caller", info.sender.to_string())
+                    .add_attribute("msgs_len", msgs.len().to_string())
+                    .add_attribute("timeout_seconds", timeout_seconds.to_string())
+                    .add_attribute("callback", format!("{:?}", callback)))
+            }
+        }
+    }
+
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn mock_note_query(
+        _deps: cosmwasm_std::Deps<cosmwasm_std::Empty>,
+        _env: cosmwasm_std::Env,
+        _msg: cosmwasm_std::Binary,
+    ) -> Result<cosmwasm_std::Binary, cosmwasm_std::StdError> {
+        to_json_binary("no queries")
+    }
+
+    fn mock_note_contract() -> Box<dyn Contract<Empty>> {
+        let contract =
+            ContractWrapper::new(mock_note_execute, mock_note_instantiate, mock_note_query);
+        Box::new(contract)
+    }
+
+    fn merger_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(execute, instantiate, query).with_migrate(migrate);
+        Box::new(contract)
+    }
+
+    fn cw20_base_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        );
+        Box::new(contract)
+    }
+
+    fn setup() -> (
+        App,
+        Addr, 
+        Addr, 
+        Addr, 
+        Addr, 
+    ) {
+        let mut app = App::default();
+
+        let cw20_code_id = app.store_code(cw20_base_contract());
+        let merger_code_id = app.store_code(merger_contract());
+        let note_code_id = app.store_code(mock_note_contract());
+
+        // instantiate the mock note
+        let note_addr = app
+            .instantiate_contract(
+                note_code_id,
+                "note_deployer".into_addr(),
+                &Empty {}, 
+                &[],
+                "Mock Note",
+                None,
+            )
+            .unwrap();
+
+        // instantiate Token A
+        let token_a_admin = "token_a_admin".into_addr();
+        let token_a_init = cw20_base::msg::InstantiateMsg {
+            name: "Token A".into(),
+            symbol: "TKNA".into(),
+            decimals: 6,
+            initial_balances: vec![],
+            mint: Some(cw20::MinterResponse {
+                minter: token_a_admin.to_string(),
+                cap: None,
+            }),
+            marketing: None,
+        };
+        let token_a_addr = app
+            .instantiate_contract(
+                cw20_code_id,
+                token_a_admin.clone(),
+                &token_a_init,
+                &[],
+                "Token A",
+                None,
+            )
+            .unwrap();
+
+        let xion_mint_addr = token_a_addr.clone();
+
+        // instantiate the Merger
+        let init_msg = InstantiateMsg {
+            owner: None,
+            guardian: Some("guardian".into_addr().to_string()),
+            note_contract: note_addr.to_string(),
+            source_tokens: vec![SourceTokenMsg {
+                token: token_a_addr.to_string(),
+                kind: TokenKind::Cw20,
+                ratio: Decimal::one(),
+                cap: None,
+                user_cap: None,
+                mode: MergeMode::Lock,
+                merkle_root: None,
+            }],
+            xion_mint_contract: xion_mint_addr.to_string(),
+            timeout_seconds: None,
+            xion_hrp: None,
+            start_time: None,
+            end_time: None,
+            fee_bps: None,
+            treasury: None,
+            batching: None,
+            approvers: None,
+        };
+        let merger_addr = app
+            .instantiate_contract(
+                merger_code_id,
+                "merger_deployer".into_addr(),
+                &init_msg,
+                &[],
+                "Merger Contract",
+                None,
+            )
+            .unwrap();
+
+        (app, merger_addr, token_a_addr, note_addr, token_a_admin)
+    }
+
+    #[test]
+    fn test_init() {
+        let (app, merger_addr, token_a_addr, note_addr, _) = setup();
+
+        let cfg: super::Config = app
+            .wrap()
+            .query_wasm_smart(&merger_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+
+        assert_eq!(cfg.note_contract, note_addr);
+
+        let ownership: OwnershipResponse = app
+            .wrap()
+            .query_wasm_smart(&merger_addr, &QueryMsg::GetOwnership {})
+            .unwrap();
+        assert_eq!(ownership.owner, "merger_deployer".into_addr().to_string());
+        assert_eq!(ownership.pending_owner, None);
+
+        let sources: SourceTokensResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &merger_addr,
+                &QueryMsg::GetSourceTokens {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(sources.source_tokens.len(), 1);
+        assert_eq!(sources.source_tokens[0].token, token_a_addr.to_string());
+        assert!(sources.source_tokens[0].enabled);
+    }
+
+    #[test]
     fn test_receive_lock() {
         let (mut app, merger_addr, token_a_addr, _note_addr, token_a_admin) = setup();
 
-        // 1) Mint some "TokenA" for a user
+        // 1) Mint some "TokenA" for a user
+        let user = "user1".into_addr();
+        let amount = Uint128::new(500);
+        app.execute_contract(
+            token_a_admin,
+            token_a_addr.clone(),
+            &Cw20ExecuteMsg::Mint {
+                recipient: user.to_string(),
+                amount,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // 2) user sends token_a to the merger
+        let lock_msg = ReceiveMsg::Lock {
+            xion_meta_account: XION_RECIPIENT.to_string(),
+            proof: None,
+        };
+        let send_msg = Cw20ExecuteMsg::Send {
+            contract: merger_addr.to_string(),
+            amount,
+            msg: to_json_binary(&lock_msg).unwrap(),
+        };
+
+        let res = app
+            .execute_contract(user.clone(), token_a_addr.clone(), &send_msg, &[])
+            .unwrap();
+
+        // 3) Check for expected events
+        let wasm_events: Vec<_> = res.events.iter().filter(|e| e.ty == "wasm").collect();
+        assert!(wasm_events.len() >= 2, "Expected at least 2 wasm events");
+
+        let found_lock_and_mint = wasm_events.iter().any(|ev| {
+            ev.attributes
+                .iter()
+                .any(|attr| attr.key == "action" && attr.value == "lock_and_mint")
+        });
+        assert!(
+            found_lock_and_mint,
+            "No lock_and_mint action found in events"
+        );
+
+        let mock_note_evt = res
+            .events
+            .iter()
+            .find(|ev| ev.attributes.iter().any(|at| at.key == "mock_note"))
+            .expect("mock note should have been called");
+
+        let mock_note_attr = mock_note_evt
+            .attributes
+            .iter()
+            .find(|at| at.key == "mock_note")
+            .unwrap();
+        assert_eq!(mock_note_attr.value, "received_execute");
+    }
+
+    #[test]
+    fn test_lock_rejects_invalid_xion_recipient() {
+        let (mut app, merger_addr, token_a_addr, _note_addr, token_a_admin) = setup();
+        let user = "user1".into_addr();
+        app.execute_contract(
+            token_a_admin,
+            token_a_addr.clone(),
+            &Cw20ExecuteMsg::Mint {
+                recipient: user.to_string(),
+                amount: Uint128::new(500),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let send_lock = |app: &mut App, recipient: &str| {
+            let send_msg = Cw20ExecuteMsg::Send {
+                contract: merger_addr.to_string(),
+                amount: Uint128::new(100),
+                msg: to_json_binary(&ReceiveMsg::Lock {
+                    xion_meta_account: recipient.to_string(),
+                    proof: None,
+                })
+                .unwrap(),
+            };
+            app.execute_contract(user.clone(), token_a_addr.clone(), &send_msg, &[])
+        };
+
+        for recipient in [
+            // wrong prefix
+            "juno1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5fs09pq",
+            // 16-byte payload
+            "xion1qypqxpq9qcrsszg2pvxq6rs0zq6q6l0k",
+            // bad checksum
+            "xion1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5atkusj",
+            // uppercase form
+            "XION1QYPQXPQ9QCRSSZG2PVXQ6RS0ZQG3YYC5ATKUSH",
+            "xion1xyz",
+        ] {
+            let err = send_lock(&mut app, recipient).unwrap_err();
+            match err.downcast::<ContractError>().unwrap() {
+                ContractError::InvalidRecipient { .. } => {}
+                e => panic!("unexpected error for {}: {}", recipient, e),
+            }
+        }
+
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                token_a_addr.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: user.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(500));
+
+        // 32-byte meta-accounts are accepted alongside 20-byte keys
+        send_lock(
+            &mut app,
+            "xion1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5z5tpwxqergd3c8g7rusq62ld7y",
+        )
+        .unwrap();
+        send_lock(&mut app, XION_RECIPIENT).unwrap();
+    }
+
+    #[test]
+    fn test_update_config_and_ownership() {
+        let (mut app, merger_addr, _token_a_addr, _note_addr, _) = setup();
+        let owner = "merger_deployer".into_addr();
+        let new_owner = "dao_core".into_addr();
+        let user = "user1".into_addr();
+
+        let update_msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            xion_mint_contract: Some("xion1newminter".to_string()),
+            ..Default::default()
+        });
+        let err = app
+            .execute_contract(user.clone(), merger_addr.clone(), &update_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let res = app
+            .execute_contract(owner.clone(), merger_addr.clone(), &update_msg, &[])
+            .unwrap();
+        assert!(res.events.iter().any(|ev| ev
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "xion_mint_contract" && attr.value == "xion1newminter")));
+        let cfg: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&merger_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(cfg.xion_mint_contract, "xion1newminter");
+        assert_eq!(cfg.timeout_seconds, 300);
+
+        // the polytone timeout is bounded
+        let err = app
+            .execute_contract(
+                owner.clone(),
+                merger_addr.clone(),
+                &ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                    timeout_seconds: Some(5),
+                    ..Default::default()
+                }),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidTimeout { min, max } => assert_eq!((min, max), (60, 86_400)),
+            e => panic!("unexpected error: {}", e),
+        }
+        app.execute_contract(
+            owner.clone(),
+            merger_addr.clone(),
+            &ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                timeout_seconds: Some(600),
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap();
+        let cfg: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&merger_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(cfg.timeout_seconds, 600);
+
+        // two-step transfer: only the proposed owner can accept
+        app.execute_contract(
+            owner.clone(),
+            merger_addr.clone(),
+            &ExecuteMsg::TransferOwnership {
+                new_owner: new_owner.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(
+                user,
+                merger_addr.clone(),
+                &ExecuteMsg::AcceptOwnership {},
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::NotPendingOwner {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        app.execute_contract(
+            new_owner.clone(),
+            merger_addr.clone(),
+            &ExecuteMsg::AcceptOwnership {},
+            &[],
+        )
+        .unwrap();
+
+        let ownership: OwnershipResponse = app
+            .wrap()
+            .query_wasm_smart(&merger_addr, &QueryMsg::GetOwnership {})
+            .unwrap();
+        assert_eq!(ownership.owner, new_owner.to_string());
+        assert_eq!(ownership.pending_owner, None);
+
+        let err = app
+            .execute_contract(owner, merger_addr, &update_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_update_config_clears_fields_and_guards_note() {
+        let (mut app, merger_addr, token_a_addr, note_addr, token_a_admin) = setup();
+        let owner = "merger_deployer".into_addr();
+        let user = "user1".into_addr();
+        let new_note = "new_note".into_addr();
+
+        // a field can't be both set and cleared
+        let err = app
+            .execute_contract(
+                owner.clone(),
+                merger_addr.clone(),
+                &ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                    guardian: Some("guardian2".into_addr().to_string()),
+                    clear: vec![OptionalConfigField::Guardian],
+                    ..Default::default()
+                }),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidConfig { reason } => {
+                assert_eq!(reason, "guardian can't be both set and cleared")
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        app.execute_contract(
+            owner.clone(),
+            merger_addr.clone(),
+            &ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                clear: vec![OptionalConfigField::Guardian],
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap();
+        let cfg: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&merger_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(cfg.guardian, None);
+
+        // the treasury can't be cleared while a fee is charged
+        app.execute_contract(
+            owner.clone(),
+            merger_addr.clone(),
+            &ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                fee_bps: Some(100),
+                treasury: Some("treasury".into_addr().to_string()),
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap();
+        let clear_treasury = |fee_bps: Option<u16>| {
+            ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                fee_bps,
+                clear: vec![OptionalConfigField::Treasury],
+                ..Default::default()
+            })
+        };
+        app.execute_contract(
+            owner.clone(),
+            merger_addr.clone(),
+            &clear_treasury(None),
+            &[],
+        )
+        .unwrap_err();
+        app.execute_contract(
+            owner.clone(),
+            merger_addr.clone(),
+            &clear_treasury(Some(0)),
+            &[],
+        )
+        .unwrap();
+        let cfg: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&merger_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!((cfg.fee_bps, cfg.treasury), (0, None));
+
+        // the note can't be swapped out from under a pending lock
+        let lock_id = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(500),
+        );
+        let change_note = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            note_contract: Some(new_note.to_string()),
+            ..Default::default()
+        });
+        let err = app
+            .execute_contract(owner.clone(), merger_addr.clone(), &change_note, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidConfig { reason } => {
+                assert_eq!(reason, "note_contract can't change while locks are pending")
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let success = Callback::Execute(Ok(polytone::callbacks::ExecutionResponse {
+            executed_by: "xion_proxy".to_string(),
+            result: vec![],
+        }));
+        app.execute_contract(
+            note_addr,
+            merger_addr.clone(),
+            &mint_callback(&app, &merger_addr, lock_id, success),
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(owner, merger_addr.clone(), &change_note, &[])
+            .unwrap();
+        let cfg: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&merger_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(cfg.note_contract, new_note.to_string());
+    }
+
+    #[test]
+    fn test_pause_blocks_locks() {
+        let (mut app, merger_addr, token_a_addr, note_addr, token_a_admin) = setup();
+        let guardian = "guardian".into_addr();
+        let user = "user1".into_addr();
+
+        let lock_id = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(100),
+        );
+
+        let err = app
+            .execute_contract(
+                user.clone(),
+                merger_addr.clone(),
+                &ExecuteMsg::Pause { reason: None },
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        app.execute_contract(
+            guardian.clone(),
+            merger_addr.clone(),
+            &ExecuteMsg::Pause {
+                reason: Some("relayer halted".to_string()),
+            },
+            &[],
+        )
+        .unwrap();
+        let pause: PauseResponse = app
+            .wrap()
+            .query_wasm_smart(&merger_addr, &QueryMsg::GetPauseStatus {})
+            .unwrap();
+        assert!(pause.paused);
+        assert_eq!(pause.reason, Some("relayer halted".to_string()));
+
+        app.execute_contract(
+            token_a_admin.clone(),
+            token_a_addr.clone(),
+            &Cw20ExecuteMsg::Mint {
+                recipient: user.to_string(),
+                amount: Uint128::new(100),
+            },
+            &[],
+        )
+        .unwrap();
+        let send_msg = Cw20ExecuteMsg::Send {
+            contract: merger_addr.to_string(),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&ReceiveMsg::Lock {
+                xion_meta_account: XION_RECIPIENT.to_string(),
+                proof: None,
+            })
+            .unwrap(),
+        };
+        let err = app
+            .execute_contract(user.clone(), token_a_addr.clone(), &send_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Paused {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // refunds for in-flight locks still settle while paused
+        app.execute_contract(
+            note_addr,
+            merger_addr.clone(),
+            &mint_callback(
+                &app,
+                &merger_addr,
+                lock_id,
+                Callback::Execute(Err("timeout".to_string())),
+            ),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(query_balance(&app, &token_a_addr, &user), Uint128::new(200));
+
+        app.execute_contract(guardian, merger_addr.clone(), &ExecuteMsg::Unpause {}, &[])
+            .unwrap();
+        app.execute_contract(user, token_a_addr, &send_msg, &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_lock_window_phases() {
+        let (mut app, merger_addr, token_a_addr, _note_addr, token_a_admin) = setup();
+        let owner = "merger_deployer".into_addr();
+        let user = "user1".into_addr();
+        let now = app.block_info().time;
+
+        app.execute_contract(
+            token_a_admin,
+            token_a_addr.clone(),
+            &Cw20ExecuteMsg::Mint {
+                recipient: user.to_string(),
+                amount: Uint128::new(300),
+            },
+            &[],
+        )
+        .unwrap();
+        let send_msg = Cw20ExecuteMsg::Send {
+            contract: merger_addr.to_string(),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&ReceiveMsg::Lock {
+                xion_meta_account: XION_RECIPIENT.to_string(),
+                proof: None,
+            })
+            .unwrap(),
+        };
+        let query_phase = |app: &App| -> PhaseResponse {
+            app.wrap()
+                .query_wasm_smart(&merger_addr, &QueryMsg::GetPhase {})
+                .unwrap()
+        };
+
+        // end must come after start
+        let err = app
+            .execute_contract(
+                owner.clone(),
+                merger_addr.clone(),
+                &ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                    start_time: Some(now.plus_seconds(200)),
+                    end_time: Some(now.plus_seconds(100)),
+                    ..Default::default()
+                }),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidConfig { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        app.execute_contract(
+            owner.clone(),
+            merger_addr.clone(),
+            &ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                start_time: Some(now.plus_seconds(100)),
+                end_time: Some(now.plus_seconds(1000)),
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap();
+
+        let phase = query_phase(&app);
+        assert_eq!(phase.phase, MergerPhase::Pending);
+        assert_eq!(phase.seconds_until_start, Some(100));
+        assert_eq!(phase.seconds_until_end, Some(1000));
+        let err = app
+            .execute_contract(user.clone(), token_a_addr.clone(), &send_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::MergerNotOpen { phase } => assert_eq!(phase, "pending"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        app.update_block(|block| block.time = now.plus_seconds(400));
+        let phase = query_phase(&app);
+        assert_eq!(phase.phase, MergerPhase::Open);
+        assert_eq!(phase.seconds_until_start, None);
+        assert_eq!(phase.seconds_until_end, Some(600));
+        app.execute_contract(user.clone(), token_a_addr.clone(), &send_msg, &[])
+            .unwrap();
+
+        // finalizing is only possible once the window has closed
+        let err = app
+            .execute_contract(
+                user.clone(),
+                merger_addr.clone(),
+                &ExecuteMsg::Finalize {},
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::MergerNotClosed {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        app.update_block(|block| block.time = now.plus_seconds(1000));
+        assert_eq!(query_phase(&app).phase, MergerPhase::Closed);
+        let err = app
+            .execute_contract(user.clone(), token_a_addr.clone(), &send_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::MergerNotOpen { phase } => assert_eq!(phase, "closed"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // anyone can finalize
+        let res = app
+            .execute_contract(
+                user.clone(),
+                merger_addr.clone(),
+                &ExecuteMsg::Finalize {},
+                &[],
+            )
+            .unwrap();
+        let attrs: Vec<_> = res
+            .events
+            .iter()
+            .flat_map(|ev| ev.attributes.iter())
+            .map(|attr| (attr.key.as_str(), attr.value.clone()))
+            .collect();
+        assert!(attrs.contains(&("lock_count", "1".to_string())));
+        assert!(attrs.contains(&("total_locked", format!("{}:100", token_a_addr))));
+
+        let phase = query_phase(&app);
+        assert_eq!(phase.phase, MergerPhase::Finalized);
+        assert_eq!(phase.finalized_at, Some(now.plus_seconds(1000)));
+        assert_eq!(phase.seconds_until_end, None);
+
+        // finalization is permanent
+        let err = app
+            .execute_contract(
+                owner,
+                merger_addr.clone(),
+                &ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                    end_time: Some(now.plus_seconds(5000)),
+                    ..Default::default()
+                }),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::MergerFinalized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let err = app
+            .execute_contract(
+                user.clone(),
+                merger_addr.clone(),
+                &ExecuteMsg::Finalize {},
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::MergerFinalized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let err = app
+            .execute_contract(user, token_a_addr, &send_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::MergerNotOpen { phase } => assert_eq!(phase, "finalized"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_approval_gate() {
+        let (mut app, _, token_a_addr, note_addr, token_a_admin) = setup();
+        let dao_a = "dao_a_core".into_addr();
+        let dao_b = "dao_b_core".into_addr();
+        let user = "user1".into_addr();
+
+        let merger_code_id = app.store_code(merger_contract());
+        let merger_addr = app
+            .instantiate_contract(
+                merger_code_id,
+                "merger_deployer".into_addr(),
+                &InstantiateMsg {
+                    owner: None,
+                    guardian: None,
+                    note_contract: note_addr.to_string(),
+                    source_tokens: vec![SourceTokenMsg {
+                        token: token_a_addr.to_string(),
+                        kind: TokenKind::Cw20,
+                        ratio: Decimal::one(),
+                        cap: None,
+                        user_cap: None,
+                        mode: MergeMode::Lock,
+                        merkle_root: None,
+                    }],
+                    xion_mint_contract: token_a_addr.to_string(),
+                    timeout_seconds: None,
+                    xion_hrp: None,
+                    start_time: None,
+                    end_time: None,
+                    fee_bps: None,
+                    treasury: None,
+                    batching: None,
+                    approvers: Some(vec![dao_a.to_string(), dao_b.to_string()]),
+                },
+                &[],
+                "Merger Contract",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            token_a_admin,
+            token_a_addr.clone(),
+            &Cw20ExecuteMsg::Mint {
+                recipient: user.to_string(),
+                amount: Uint128::new(200),
+            },
+            &[],
+        )
+        .unwrap();
+        let send_msg = Cw20ExecuteMsg::Send {
+            contract: merger_addr.to_string(),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&ReceiveMsg::Lock {
+                xion_meta_account: XION_RECIPIENT.to_string(),
+                proof: None,
+            })
+            .unwrap(),
+        };
+        let query_approvals = |app: &App| -> ApprovalsResponse {
+            app.wrap()
+                .query_wasm_smart(&merger_addr, &QueryMsg::GetApprovals {})
+                .unwrap()
+        };
+
+        let err = app
+            .execute_contract(user.clone(), token_a_addr.clone(), &send_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::MergerNotOpen { phase } => assert_eq!(phase, "awaiting_approval"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let err = app
+            .execute_contract(
+                user.clone(),
+                merger_addr.clone(),
+                &ExecuteMsg::Approve {},
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::NotApprover {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        app.execute_contract(
+            dao_a.clone(),
+            merger_addr.clone(),
+            &ExecuteMsg::Approve {},
+            &[],
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(
+                dao_a.clone(),
+                merger_addr.clone(),
+                &ExecuteMsg::Approve {},
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::AlreadyApproved { approver } => assert_eq!(approver, dao_a.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+        let approvals = query_approvals(&app);
+        assert!(!approvals.approved);
+        // listed in address order
+        let mut expected = vec![
+            Approval {
+                approver: dao_a.to_string(),
+                approved_at: Some(app.block_info().time),
+            },
+            Approval {
+                approver: dao_b.to_string(),
+                approved_at: None,
+            },
+        ];
+        expected.sort_by(|a, b| a.approver.cmp(&b.approver));
+        assert_eq!(approvals.approvals, expected);
+        app.execute_contract(user.clone(), token_a_addr.clone(), &send_msg, &[])
+            .unwrap_err();
+
+        // the last approval opens the merger
+        app.execute_contract(dao_b, merger_addr.clone(), &ExecuteMsg::Approve {}, &[])
+            .unwrap();
+        assert!(query_approvals(&app).approved);
+        let phase: PhaseResponse = app
+            .wrap()
+            .query_wasm_smart(&merger_addr, &QueryMsg::GetPhase {})
+            .unwrap();
+        assert_eq!(phase.phase, MergerPhase::Open);
+        app.execute_contract(user, token_a_addr, &send_msg, &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_set_approvers() {
+        let (mut app, merger_addr, _token_a_addr, _note_addr, _token_a_admin) = setup();
+        let owner = "merger_deployer".into_addr();
+        let dao_a = "dao_a".into_addr();
+        let dao_b = "dao_b".into_addr();
+        let dao_c = "dao_c".into_addr();
+        let now = app.block_info().time;
+        let set_approvers = |approvers: &[&Addr]| ExecuteMsg::SetApprovers {
+            approvers: approvers.iter().map(|a| a.to_string()).collect(),
+        };
+        let query_approvals = |app: &App| -> ApprovalsResponse {
+            app.wrap()
+                .query_wasm_smart(&merger_addr, &QueryMsg::GetApprovals {})
+                .unwrap()
+        };
+
+        // without approvers the merger is already open
+        let err = app
+            .execute_contract(
+                owner.clone(),
+                merger_addr.clone(),
+                &set_approvers(&[&dao_a]),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::ApproversFrozen { phase } => assert_eq!(phase, "open"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        app.execute_contract(
+            owner.clone(),
+            merger_addr.clone(),
+            &ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                start_time: Some(now.plus_seconds(100)),
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(
+                dao_a.clone(),
+                merger_addr.clone(),
+                &set_approvers(&[&dao_a]),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        app.execute_contract(
+            owner.clone(),
+            merger_addr.clone(),
+            &set_approvers(&[&dao_a, &dao_b]),
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            dao_a.clone(),
+            merger_addr.clone(),
+            &ExecuteMsg::Approve {},
+            &[],
+        )
+        .unwrap();
+
+        // dao_a keeps its approval, dao_b is dropped for dao_c
+        app.execute_contract(
+            owner.clone(),
+            merger_addr.clone(),
+            &set_approvers(&[&dao_a, &dao_c]),
+            &[],
+        )
+        .unwrap();
+        let mut expected = vec![
+            Approval {
+                approver: dao_a.to_string(),
+                approved_at: Some(app.block_info().time),
+            },
+            Approval {
+                approver: dao_c.to_string(),
+                approved_at: None,
+            },
+        ];
+        expected.sort_by(|a, b| a.approver.cmp(&b.approver));
+        assert_eq!(query_approvals(&app).approvals, expected);
+        let err = app
+            .execute_contract(dao_b, merger_addr.clone(), &ExecuteMsg::Approve {}, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::NotApprover {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // still changeable while waiting on approvals, not once open
+        app.update_block(|block| block.time = now.plus_seconds(100));
+        app.execute_contract(
+            owner.clone(),
+            merger_addr.clone(),
+            &set_approvers(&[&dao_a, &dao_c]),
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(dao_c, merger_addr.clone(), &ExecuteMsg::Approve {}, &[])
+            .unwrap();
+        assert!(query_approvals(&app).approved);
+        let err = app
+            .execute_contract(owner, merger_addr.clone(), &set_approvers(&[&dao_a]), &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::ApproversFrozen { phase } => assert_eq!(phase, "open"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_source_token_registry() {
+        let (mut app, merger_addr, token_a_addr, _note_addr, token_a_admin) = setup();
+        let owner = "merger_deployer".into_addr();
+        let user = "user1".into_addr();
+        let token_c = "token_c".into_addr();
+
+        // only the owner may manage the registry
+        let add_msg = ExecuteMsg::AddSourceToken(SourceTokenMsg {
+            token: token_c.to_string(),
+            kind: TokenKind::Cw20,
+            ratio: Decimal::percent(250),
+            cap: Some(Uint128::new(1000)),
+            user_cap: None,
+            mode: MergeMode::Lock,
+            merkle_root: None,
+        });
+        let err = app
+            .execute_contract(user.clone(), merger_addr.clone(), &add_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        app.execute_contract(owner.clone(), merger_addr.clone(), &add_msg, &[])
+            .unwrap();
+
+        let source: SourceTokenResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &merger_addr,
+                &QueryMsg::GetSourceToken {
+                    token: token_c.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(source.ratio, Decimal::percent(250));
+        assert_eq!(source.cap, Some(Uint128::new(1000)));
+
+        // disabled tokens cannot be locked
+        app.execute_contract(
+            owner.clone(),
+            merger_addr.clone(),
+            &ExecuteMsg::DisableSourceToken {
+                token: token_a_addr.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            token_a_admin,
+            token_a_addr.clone(),
+            &Cw20ExecuteMsg::Mint {
+                recipient: user.to_string(),
+                amount: Uint128::new(100),
+            },
+            &[],
+        )
+        .unwrap();
+        let send_msg = Cw20ExecuteMsg::Send {
+            contract: merger_addr.to_string(),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&ReceiveMsg::Lock {
+                xion_meta_account: XION_RECIPIENT.to_string(),
+                proof: None,
+            })
+            .unwrap(),
+        };
+        let err = app
+            .execute_contract(user.clone(), token_a_addr.clone(), &send_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::SourceTokenDisabled { token } => {
+                assert_eq!(token, token_a_addr.to_string())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // removed tokens are rejected outright
+        app.execute_contract(
+            owner,
+            merger_addr.clone(),
+            &ExecuteMsg::RemoveSourceToken {
+                token: token_a_addr.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(user, token_a_addr, &send_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidToken {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_source_token_cap() {
+        let (mut app, merger_addr, token_a_addr, note_addr, token_a_admin) = setup();
+        let owner = "merger_deployer".into_addr();
+        let user = "user1".into_addr();
+
+        // re-register token a with a cap of 500
+        app.execute_contract(
+            owner.clone(),
+            merger_addr.clone(),
+            &ExecuteMsg::RemoveSourceToken {
+                token: token_a_addr.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            owner,
+            merger_addr.clone(),
+            &ExecuteMsg::AddSourceToken(SourceTokenMsg {
+                token: token_a_addr.to_string(),
+                kind: TokenKind::Cw20,
+                ratio: Decimal::one(),
+                cap: Some(Uint128::new(500)),
+                user_cap: None,
+                mode: MergeMode::Lock,
+                merkle_root: None,
+            }),
+            &[],
+        )
+        .unwrap();
+
+        let lock_id = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(400),
+        );
+
+        app.execute_contract(
+            token_a_admin,
+            token_a_addr.clone(),
+            &Cw20ExecuteMsg::Mint {
+                recipient: user.to_string(),
+                amount: Uint128::new(200),
+            },
+            &[],
+        )
+        .unwrap();
+        let send_msg = Cw20ExecuteMsg::Send {
+            contract: merger_addr.to_string(),
+            amount: Uint128::new(200),
+            msg: to_json_binary(&ReceiveMsg::Lock {
+                xion_meta_account: XION_RECIPIENT.to_string(),
+                proof: None,
+            })
+            .unwrap(),
+        };
+        let err = app
+            .execute_contract(user.clone(), token_a_addr.clone(), &send_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::CapExceeded { remaining } => assert_eq!(remaining, Uint128::new(100)),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // a refund frees up capacity again
+        app.execute_contract(
+            note_addr,
+            merger_addr.clone(),
+            &mint_callback(
+                &app,
+                &merger_addr,
+                lock_id,
+                Callback::Execute(Err("timeout".to_string())),
+            ),
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(user, token_a_addr, &send_msg, &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_per_user_cap() {
+        let (mut app, merger_addr, token_a_addr, _note_addr, token_a_admin) = setup();
+        let owner = "merger_deployer".into_addr();
+        let alice = "alice".into_addr();
+        let bob = "bob".into_addr();
+
+        let caps_msg = ExecuteMsg::SetSourceTokenCaps {
+            token: token_a_addr.to_string(),
+            cap: Some(Uint128::new(1000)),
+            user_cap: Some(Uint128::new(300)),
+        };
+        let err = app
+            .execute_contract(alice.clone(), merger_addr.clone(), &caps_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        app.execute_contract(owner, merger_addr.clone(), &caps_msg, &[])
+            .unwrap();
+
+        mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &alice,
+            Uint128::new(250),
+        );
+
+        let capacity: UserCapacityResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &merger_addr,
+                &QueryMsg::GetUserCapacity {
+                    user: alice.to_string(),
+                    token: token_a_addr.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(capacity.locked, Uint128::new(250));
+        assert_eq!(capacity.remaining, Some(Uint128::new(50)));
+
+        app.execute_contract(
+            token_a_admin.clone(),
+            token_a_addr.clone(),
+            &Cw20ExecuteMsg::Mint {
+                recipient: alice.to_string(),
+                amount: Uint128::new(100),
+            },
+            &[],
+        )
+        .unwrap();
+        let send_msg = Cw20ExecuteMsg::Send {
+            contract: merger_addr.to_string(),
+            amount: Uint128::new(100),
+            msg: to_json_binary(&ReceiveMsg::Lock {
+                xion_meta_account: XION_RECIPIENT.to_string(),
+                proof: None,
+            })
+            .unwrap(),
+        };
+        let err = app
+            .execute_contract(alice, token_a_addr.clone(), &send_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::CapExceeded { remaining } => assert_eq!(remaining, Uint128::new(50)),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // other addresses keep their own allowance
+        mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &bob,
+            Uint128::new(300),
+        );
+
+        let source: SourceTokenResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &merger_addr,
+                &QueryMsg::GetSourceToken {
+                    token: token_a_addr.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(source.user_cap, Some(Uint128::new(300)));
+        assert_eq!(source.total_locked, Uint128::new(550));
+        assert_eq!(source.remaining, Some(Uint128::new(450)));
+    }
+
+    #[test]
+    fn test_merkle_allowlist() {
+        let (mut app, merger_addr, token_a_addr, _note_addr, token_a_admin) = setup();
+        let owner = "merger_deployer".into_addr();
+        let user = "user1".into_addr();
+        let other = "user2".into_addr();
+
+        let leaves = vec![
+            crate::merkle::leaf_hash(user.as_str(), Uint128::new(300)),
+            crate::merkle::leaf_hash(other.as_str(), Uint128::new(100)),
+        ];
+        let layers = crate::merkle::build_layers(leaves);
+        let root = hex::encode(layers.last().unwrap()[0]);
+        let user_proof = EligibilityProof {
+            max_amount: Uint128::new(300),
+            proof: crate::merkle::build_proof(&layers, 0)
+                .iter()
+                .map(hex::encode)
+                .collect(),
+        };
+
+        let set_root = |root: &str| ExecuteMsg::SetMerkleRoot {
+            token: token_a_addr.to_string(),
+            root: Some(root.to_string()),
+        };
+        let err = app
+            .execute_contract(owner.clone(), merger_addr.clone(), &set_root("abcd"), &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidMerkleRoot { root } => assert_eq!(root, "abcd"),
+            e => panic!("unexpected error: {}", e),
+        }
+        app.execute_contract(owner, merger_addr.clone(), &set_root(&root), &[])
+            .unwrap();
+
+        app.execute_contract(
+            token_a_admin,
+            token_a_addr.clone(),
+            &Cw20ExecuteMsg::Mint {
+                recipient: user.to_string(),
+                amount: Uint128::new(1000),
+            },
+            &[],
+        )
+        .unwrap();
+        let lock = |app: &mut App, amount: u128, proof: Option<EligibilityProof>| {
+            app.execute_contract(
+                user.clone(),
+                token_a_addr.clone(),
+                &Cw20ExecuteMsg::Send {
+                    contract: merger_addr.to_string(),
+                    amount: Uint128::new(amount),
+                    msg: to_json_binary(&ReceiveMsg::Lock {
+                        xion_meta_account: XION_RECIPIENT.to_string(),
+                        proof,
+                    })
+                    .unwrap(),
+                },
+                &[],
+            )
+        };
+
+        let err = lock(&mut app, 100, None).unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::MissingProof {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // claiming a bigger allowance than the snapshot breaks the proof
+        let inflated = EligibilityProof {
+            max_amount: Uint128::new(1000),
+            ..user_proof.clone()
+        };
+        let err = lock(&mut app, 100, Some(inflated)).unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidProof {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        lock(&mut app, 200, Some(user_proof.clone())).unwrap();
+        let err = lock(&mut app, 200, Some(user_proof.clone())).unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::EligibilityExceeded { remaining } => {
+                assert_eq!(remaining, Uint128::new(100))
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        lock(&mut app, 100, Some(user_proof)).unwrap();
+    }
+
+    #[test]
+    fn test_lock_native_denom() {
+        let (mut app, merger_addr, _token_a_addr, note_addr, _) = setup();
+        let owner = "merger_deployer".into_addr();
+        let user = "user1".into_addr();
+
+        app.init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &user, coins(1000, "ujuno"))
+                .unwrap()
+        });
+
+        let lock_msg = ExecuteMsg::Lock {
+            xion_meta_account: XION_RECIPIENT.to_string(),
+            proof: None,
+        };
+
+        // not whitelisted yet
+        let err = app
+            .execute_contract(
+                user.clone(),
+                merger_addr.clone(),
+                &lock_msg,
+                &coins(400, "ujuno"),
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidToken {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        app.execute_contract(
+            owner,
+            merger_addr.clone(),
+            &ExecuteMsg::AddSourceToken(SourceTokenMsg {
+                token: "ujuno".to_string(),
+                kind: TokenKind::Native,
+                ratio: Decimal::percent(200),
+                cap: None,
+                user_cap: None,
+                mode: MergeMode::Lock,
+                merkle_root: None,
+            }),
+            &[],
+        )
+        .unwrap();
+
+        let res = app
+            .execute_contract(
+                user.clone(),
+                merger_addr.clone(),
+                &lock_msg,
+                &coins(400, "ujuno"),
+            )
+            .unwrap();
+        let lock_id: u64 = res
+            .events
+            .iter()
+            .flat_map(|ev| ev.attributes.iter())
+            .find(|attr| attr.key == "lock_id")
+            .unwrap()
+            .value
+            .parse()
+            .unwrap();
+
+        let lock = query_lock(&app, &merger_addr, lock_id);
+        assert_eq!(lock.token, "ujuno");
+        assert_eq!(lock.amount, Uint128::new(400));
+        assert_eq!(lock.mint_amount, Uint128::new(800));
+
+        // a timed out mint sends the coins back through the bank
+        app.execute_contract(
+            note_addr,
+            merger_addr.clone(),
+            &mint_callback(
+                &app,
+                &merger_addr,
+                lock_id,
+                Callback::Execute(Err("timeout".to_string())),
+            ),
+            &[],
+        )
+        .unwrap();
+        let balance = app.wrap().query_balance(&user, "ujuno").unwrap();
+        assert_eq!(balance.amount, Uint128::new(1000));
+    }
+
+    #[test]
+    fn test_migrate_legacy_deployment() {
+        use cosmwasm_std::testing::{mock_dependencies, mock_env};
+        use cosmwasm_std::{ContractResult, SystemError, SystemResult, WasmQuery};
+
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { .. } => SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&cw20::BalanceResponse {
+                    balance: Uint128::new(700),
+                })
+                .unwrap(),
+            )),
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".to_string(),
+            }),
+        });
+
+        crate::state::LEGACY_CONFIG
+            .save(
+                deps.as_mut().storage,
+                &crate::state::LegacyConfig {
+                    note_contract: Addr::unchecked("note"),
+                    token_a: Addr::unchecked("token_a"),
+                    token_b: Addr::unchecked("token_b"),
+                    xion_mint_contract: "xion1minter".to_string(),
+                },
+            )
+            .unwrap();
+
+        // legacy deployments need an owner to be named
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None }).unwrap_err();
+        match err {
+            ContractError::MissingOwner {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                owner: Some("dao_core".to_string()),
+            },
+        )
+        .unwrap();
+
+        let version = cw2::get_contract_version(&deps.storage).unwrap();
+        assert_eq!(version.contract, CONTRACT_NAME);
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        let config = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(config.note_contract, Addr::unchecked("note"));
+        assert_eq!(config.xion_mint_contract, "xion1minter");
+        assert_eq!(
+            OWNERSHIP.load(&deps.storage).unwrap().owner,
+            Addr::unchecked("dao_core")
+        );
+        for token in ["token_a", "token_b"] {
+            let source = SOURCE_TOKENS.load(&deps.storage, token).unwrap();
+            assert_eq!(source.ratio, Decimal::one());
+            assert!(source.enabled);
+            assert_eq!(
+                TOTAL_LOCKED.load(&deps.storage, token).unwrap(),
+                Uint128::new(700)
+            );
+            assert_eq!(
+                CUMULATIVE_LOCKED.load(&deps.storage, token).unwrap(),
+                Uint128::new(700)
+            );
+            assert_eq!(
+                RELEASABLE.load(&deps.storage, token).unwrap(),
+                Uint128::new(700)
+            );
+        }
+
+        // a versioned deployment of another contract is refused
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:other", "9.9.9").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None }).unwrap_err();
+        match err {
+            ContractError::CannotMigrate {
+                previous_contract, ..
+            } => assert_eq!(previous_contract, "crates.io:other"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_convert_amount_rounds_down() {
+        assert_eq!(
+            convert_amount(Uint128::new(100), Decimal::percent(250)).unwrap(),
+            Uint128::new(250)
+        );
+        // 3 * 0.8 = 2.4
+        assert_eq!(
+            convert_amount(Uint128::new(3), Decimal::percent(80)).unwrap(),
+            Uint128::new(2)
+        );
+        match convert_amount(Uint128::new(1), Decimal::percent(50)).unwrap_err() {
+            ContractError::InvalidAmount {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    fn mint_and_lock(
+        app: &mut App,
+        merger_addr: &Addr,
+        token_addr: &Addr,
+        token_admin: &Addr,
+        user: &Addr,
+        amount: Uint128,
+    ) -> u64 {
+        app.execute_contract(
+            token_admin.clone(),
+            token_addr.clone(),
+            &Cw20ExecuteMsg::Mint {
+                recipient: user.to_string(),
+                amount,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let send_msg = Cw20ExecuteMsg::Send {
+            contract: merger_addr.to_string(),
+            amount,
+            msg: to_json_binary(&ReceiveMsg::Lock {
+                xion_meta_account: XION_RECIPIENT.to_string(),
+                proof: None,
+            })
+            .unwrap(),
+        };
+        let res = app
+            .execute_contract(user.clone(), token_addr.clone(), &send_msg, &[])
+            .unwrap();
+
+        res.events
+            .iter()
+            .flat_map(|ev| ev.attributes.iter())
+            .find(|attr| attr.key == "lock_id")
+            .expect("lock should emit its id")
+            .value
+            .parse()
+            .unwrap()
+    }
+
+    fn mint_callback(app: &App, merger_addr: &Addr, lock_id: u64, result: Callback) -> ExecuteMsg {
+        let lock = query_lock(app, merger_addr, lock_id);
+        let payload = MintCallback {
+            lock_id,
+            sender: lock.sender,
+            token: lock.token,
+            amount: lock.amount,
+        };
+        ExecuteMsg::Callback(CallbackMessage {
+            initiator: merger_addr.clone(),
+            initiator_msg: to_json_binary(&payload).unwrap(),
+            result,
+        })
+    }
+
+    fn query_lock(app: &App, merger_addr: &Addr, lock_id: u64) -> LockResponse {
+        app.wrap()
+            .query_wasm_smart(merger_addr, &QueryMsg::GetLock { lock_id })
+            .unwrap()
+    }
+
+    fn query_balance(app: &App, token_addr: &Addr, address: &Addr) -> Uint128 {
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                token_addr,
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        balance.balance
+    }
+
+    #[test]
+    fn test_fee_paid_to_treasury_on_mint() {
+        let (mut app, merger_addr, token_a_addr, note_addr, token_a_admin) = setup();
+        let owner = "merger_deployer".into_addr();
+        let treasury = "treasury".into_addr();
+        let user = "user1".into_addr();
+
+        let set_fee = |fee_bps: u16, treasury: Option<String>| {
+            ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                fee_bps: Some(fee_bps),
+                treasury,
+                ..Default::default()
+            })
+        };
+        let err = app
+            .execute_contract(owner.clone(), merger_addr.clone(), &set_fee(100, None), &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidConfig { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let err = app
+            .execute_contract(
+                owner.clone(),
+                merger_addr.clone(),
+                &set_fee(1_001, Some(treasury.to_string())),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidFee { max_bps } => assert_eq!(max_bps, 1_000),
+            e => panic!("unexpected error: {}", e),
+        }
+        app.execute_contract(
+            owner,
+            merger_addr.clone(),
+            &set_fee(100, Some(treasury.to_string())),
+            &[],
+        )
+        .unwrap();
+
+        // 1% of the lock is held back from the mint
+        let minted_id = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(1000),
+        );
+        let lock = query_lock(&app, &merger_addr, minted_id);
+        assert_eq!(lock.fee, Uint128::new(10));
+        assert_eq!(lock.mint_amount, Uint128::new(990));
+
+        let success = Callback::Execute(Ok(polytone::callbacks::ExecutionResponse {
+            executed_by: "xion_proxy".to_string(),
+            result: vec![],
+        }));
+        app.execute_contract(
+            note_addr.clone(),
+            merger_addr.clone(),
+            &mint_callback(&app, &merger_addr, minted_id, success),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            query_balance(&app, &token_a_addr, &treasury),
+            Uint128::new(10)
+        );
+
+        // a failed mint refunds the whole lock, fee included
+        let failed_id = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(500),
+        );
+        app.execute_contract(
+            note_addr,
+            merger_addr.clone(),
+            &mint_callback(
+                &app,
+                &merger_addr,
+                failed_id,
+                Callback::Execute(Err("out of gas".to_string())),
+            ),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(query_balance(&app, &token_a_addr, &user), Uint128::new(500));
+
+        let fees: FeesCollectedResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &merger_addr,
+                &QueryMsg::GetFeesCollected {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            fees.fees,
+            vec![FeeCollected {
+                token: token_a_addr.to_string(),
+                amount: Uint128::new(10),
+            }]
+        );
+        let source: SourceTokenResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &merger_addr,
+                &QueryMsg::GetSourceToken {
+                    token: token_a_addr.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(source.total_locked, Uint128::new(990));
+        assert_eq!(source.fees_collected, Uint128::new(10));
+    }
+
+    #[test]
+    fn test_deposit_and_flush_basket() {
+        let (mut app, merger_addr, token_a_addr, note_addr, token_a_admin) = setup();
+        let user = "user1".into_addr();
+
+        app.execute_contract(
+            token_a_admin,
+            token_a_addr.clone(),
+            &Cw20ExecuteMsg::Mint {
+                recipient: user.to_string(),
+                amount: Uint128::new(300),
+            },
+            &[],
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(
+                user.clone(),
+                merger_addr.clone(),
+                &ExecuteMsg::Flush {},
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::EmptyBasket {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        for amount in [100u128, 200] {
+            let send_msg = Cw20ExecuteMsg::Send {
+                contract: merger_addr.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_json_binary(&ReceiveMsg::Deposit {
+                    xion_meta_account: XION_RECIPIENT.to_string(),
+                    proof: None,
+                })
+                .unwrap(),
+            };
+            let res = app
+                .execute_contract(user.clone(), token_a_addr.clone(), &send_msg, &[])
+                .unwrap();
+            // deposits wait for the flush instead of reaching the note
+            assert!(!res
+                .events
+                .iter()
+                .any(|ev| ev.attributes.iter().any(|at| at.key == "mock_note")));
+        }
+
+        let basket: LocksResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &merger_addr,
+                &QueryMsg::GetBasket {
+                    user: user.to_string(),
+                },
+            )
+            .unwrap();
+        let lock_ids: Vec<u64> = basket.locks.iter().map(|lock| lock.lock_id).collect();
+        assert_eq!(lock_ids, vec![1, 2]);
+        assert!(basket
+            .locks
+            .iter()
+            .all(|lock| lock.status == LockStatus::Queued));
+
+        let res = app
+            .execute_contract(
+                user.clone(),
+                merger_addr.clone(),
+                &ExecuteMsg::Flush {},
+                &[],
+            )
+            .unwrap();
+        let note_evt = res
+            .events
+            .iter()
+            .find(|ev| ev.attributes.iter().any(|at| at.key == "mock_note"))
+            .expect("flush should reach the note");
+        assert!(note_evt
+            .attributes
+            .iter()
+            .any(|at| at.key == "msgs_len" && at.value == "2"));
+        assert_eq!(
+            query_lock(&app, &merger_addr, 1).status,
+            LockStatus::Pending
+        );
+
+        // one failed packet refunds every lock it carried
+        app.execute_contract(
+            note_addr,
+            merger_addr.clone(),
+            &ExecuteMsg::Callback(CallbackMessage {
+                initiator: merger_addr.clone(),
+                initiator_msg: to_json_binary(&FlushCallback { lock_ids }).unwrap(),
+                result: Callback::Execute(Err("timeout".to_string())),
+            }),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            query_lock(&app, &merger_addr, 2).status,
+            LockStatus::TimedOut
+        );
+        assert_eq!(query_balance(&app, &token_a_addr, &user), Uint128::new(300));
+
+        let err = app
+            .execute_contract(user, merger_addr, &ExecuteMsg::Flush {}, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::EmptyBasket {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_process_batch() {
+        let (mut app, merger_addr, token_a_addr, note_addr, token_a_admin) = setup();
+        let owner = "merger_deployer".into_addr();
         let user = "user1".into_addr();
-        let amount = Uint128::new(500);
+        let keeper = "keeper".into_addr();
+
+        app.execute_contract(
+            owner,
+            merger_addr.clone(),
+            &ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                batching: Some(true),
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap();
+
+        let first = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(100),
+        );
+        mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(200),
+        );
+        assert_eq!(
+            query_lock(&app, &merger_addr, first).status,
+            LockStatus::Queued
+        );
+        let queue = |app: &App| -> u32 {
+            let res: BatchQueueResponse = app
+                .wrap()
+                .query_wasm_smart(&merger_addr, &QueryMsg::GetBatchQueue {})
+                .unwrap();
+            res.queued
+        };
+        assert_eq!(queue(&app), 2);
+
+        // anyone can process the queue, oldest locks first
+        let res = app
+            .execute_contract(
+                keeper.clone(),
+                merger_addr.clone(),
+                &ExecuteMsg::ProcessBatch { limit: Some(1) },
+                &[],
+            )
+            .unwrap();
+        assert!(res.events.iter().any(|ev| ev
+            .attributes
+            .iter()
+            .any(|at| at.key == "msgs_len" && at.value == "1")));
+        assert_eq!(queue(&app), 1);
+        assert_eq!(
+            query_lock(&app, &merger_addr, first).status,
+            LockStatus::Pending
+        );
+
+        app.execute_contract(
+            keeper.clone(),
+            merger_addr.clone(),
+            &ExecuteMsg::ProcessBatch { limit: None },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(queue(&app), 0);
+        let err = app
+            .execute_contract(
+                keeper,
+                merger_addr.clone(),
+                &ExecuteMsg::ProcessBatch { limit: None },
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::EmptyBatch {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let success = Callback::Execute(Ok(polytone::callbacks::ExecutionResponse {
+            executed_by: "xion_proxy".to_string(),
+            result: vec![],
+        }));
+        app.execute_contract(
+            note_addr,
+            merger_addr.clone(),
+            &ExecuteMsg::Callback(CallbackMessage {
+                initiator: merger_addr.clone(),
+                initiator_msg: to_json_binary(&FlushCallback {
+                    lock_ids: vec![first],
+                })
+                .unwrap(),
+                result: success,
+            }),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            query_lock(&app, &merger_addr, first).status,
+            LockStatus::Minted
+        );
+    }
+
+    #[test]
+    fn test_callback_marks_lock_minted() {
+        let (mut app, merger_addr, token_a_addr, note_addr, token_a_admin) = setup();
+        let user = "user1".into_addr();
+
+        let lock_id = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(500),
+        );
+        assert_eq!(
+            query_lock(&app, &merger_addr, lock_id).status,
+            LockStatus::Pending
+        );
+
+        let success = Callback::Execute(Ok(polytone::callbacks::ExecutionResponse {
+            executed_by: "xion_proxy".to_string(),
+            result: vec![],
+        }));
+        app.execute_contract(
+            note_addr.clone(),
+            merger_addr.clone(),
+            &mint_callback(&app, &merger_addr, lock_id, success.clone()),
+            &[],
+        )
+        .unwrap();
+
+        let lock = query_lock(&app, &merger_addr, lock_id);
+        assert_eq!(lock.status, LockStatus::Minted);
+        assert_eq!(lock.amount, Uint128::new(500));
+        assert_eq!(lock.sender, user.to_string());
+        assert_eq!(
+            query_balance(&app, &token_a_addr, &merger_addr),
+            Uint128::new(500)
+        );
+
+        // a second ack for the same lock is rejected
+        let err = app
+            .execute_contract(
+                note_addr,
+                merger_addr.clone(),
+                &mint_callback(&app, &merger_addr, lock_id, success),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::LockAlreadySettled { lock_id: id } => assert_eq!(id, lock_id),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_burn_mode() {
+        let (mut app, merger_addr, token_a_addr, note_addr, token_a_admin) = setup();
+        let owner = "merger_deployer".into_addr();
+        let user = "user1".into_addr();
+        let set_mode = |immediate: bool| ExecuteMsg::SetMergeMode {
+            token: token_a_addr.to_string(),
+            mode: MergeMode::Burn { immediate },
+        };
+
+        let err = app
+            .execute_contract(user.clone(), merger_addr.clone(), &set_mode(false), &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        app.execute_contract(owner.clone(), merger_addr.clone(), &set_mode(false), &[])
+            .unwrap();
+        app.execute_contract(
+            owner.clone(),
+            merger_addr.clone(),
+            &ExecuteMsg::SetSourceTokenCaps {
+                token: token_a_addr.to_string(),
+                cap: Some(Uint128::new(300)),
+                user_cap: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // held until the mint succeeds, then burned
+        let lock_id = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(100),
+        );
+        assert_eq!(
+            query_balance(&app, &token_a_addr, &merger_addr),
+            Uint128::new(100)
+        );
+        let success = Callback::Execute(Ok(polytone::callbacks::ExecutionResponse {
+            executed_by: "xion_proxy".to_string(),
+            result: vec![],
+        }));
+        app.execute_contract(
+            note_addr.clone(),
+            merger_addr.clone(),
+            &mint_callback(&app, &merger_addr, lock_id, success),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            query_balance(&app, &token_a_addr, &merger_addr),
+            Uint128::zero()
+        );
+
+        // burned on receipt, so a failed mint has nothing to refund
+        app.execute_contract(owner, merger_addr.clone(), &set_mode(true), &[])
+            .unwrap();
+        let lock_id = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(200),
+        );
+        assert_eq!(
+            query_balance(&app, &token_a_addr, &merger_addr),
+            Uint128::zero()
+        );
+        app.execute_contract(
+            note_addr,
+            merger_addr.clone(),
+            &mint_callback(
+                &app,
+                &merger_addr,
+                lock_id,
+                Callback::Execute(Err("codespace: wasm, code: 5".to_string())),
+            ),
+            &[],
+        )
+        .unwrap();
+        let lock = query_lock(&app, &merger_addr, lock_id);
+        assert_eq!(lock.status, LockStatus::Failed);
+        assert_eq!(lock.mode, MergeMode::Burn { immediate: true });
+        assert_eq!(query_balance(&app, &token_a_addr, &user), Uint128::zero());
+
+        let source: SourceTokenResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &merger_addr,
+                &QueryMsg::GetSourceToken {
+                    token: token_a_addr.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(source.total_locked, Uint128::zero());
+        assert_eq!(source.cumulative_locked, Uint128::new(300));
+        assert_eq!(source.releasable, Uint128::zero());
+        assert_eq!(source.total_burned, Uint128::new(300));
+        assert_eq!(source.remaining, Some(Uint128::zero()));
+        let info: cw20::TokenInfoResponse = app
+            .wrap()
+            .query_wasm_smart(&token_a_addr, &cw20::Cw20QueryMsg::TokenInfo {})
+            .unwrap();
+        assert_eq!(info.total_supply, Uint128::zero());
+
+        // burning doesn't free up room under the cap
         app.execute_contract(
             token_a_admin,
             token_a_addr.clone(),
             &Cw20ExecuteMsg::Mint {
                 recipient: user.to_string(),
-                amount,
+                amount: Uint128::new(1),
             },
             &[],
         )
         .unwrap();
-
-        // 2) user sends token_a to the merger
-        let lock_msg = ReceiveMsg::Lock {
-            xion_meta_account: "xion1xyz".to_string(),
-        };
         let send_msg = Cw20ExecuteMsg::Send {
             contract: merger_addr.to_string(),
-            amount,
-            msg: to_binary(&lock_msg).unwrap(),
+            amount: Uint128::new(1),
+            msg: to_json_binary(&ReceiveMsg::Lock {
+                xion_meta_account: XION_RECIPIENT.to_string(),
+                proof: None,
+            })
+            .unwrap(),
         };
+        let err = app
+            .execute_contract(user, token_a_addr, &send_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::CapExceeded { remaining } => assert_eq!(remaining, Uint128::zero()),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
 
-        let res = app
-            .execute_contract(user.clone(), token_a_addr.clone(), &send_msg, &[])
+    #[test]
+    fn test_callback_timeout_and_failure_refund() {
+        let (mut app, merger_addr, token_a_addr, note_addr, token_a_admin) = setup();
+        let user = "user1".into_addr();
+
+        let timed_out = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(100),
+        );
+        let failed = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(200),
+        );
+
+        app.execute_contract(
+            note_addr.clone(),
+            merger_addr.clone(),
+            &mint_callback(
+                &app,
+                &merger_addr,
+                timed_out,
+                Callback::Execute(Err("timeout".to_string())),
+            ),
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            note_addr,
+            merger_addr.clone(),
+            &mint_callback(
+                &app,
+                &merger_addr,
+                failed,
+                Callback::Execute(Err("codespace: wasm, code: 5".to_string())),
+            ),
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(
+            query_lock(&app, &merger_addr, timed_out).status,
+            LockStatus::TimedOut
+        );
+        assert_eq!(
+            query_lock(&app, &merger_addr, failed).status,
+            LockStatus::Failed
+        );
+
+        // both locks went back to the user
+        assert_eq!(query_balance(&app, &token_a_addr, &user), Uint128::new(300));
+        assert_eq!(
+            query_balance(&app, &token_a_addr, &merger_addr),
+            Uint128::zero()
+        );
+    }
+
+    #[test]
+    fn test_lock_ledger_queries() {
+        let (mut app, merger_addr, token_a_addr, note_addr, token_a_admin) = setup();
+        let alice = "alice".into_addr();
+        let bob = "bob".into_addr();
+
+        let first = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &alice,
+            Uint128::new(100),
+        );
+        let second = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &bob,
+            Uint128::new(200),
+        );
+        let third = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &alice,
+            Uint128::new(300),
+        );
+
+        app.execute_contract(
+            note_addr,
+            merger_addr.clone(),
+            &mint_callback(
+                &app,
+                &merger_addr,
+                second,
+                Callback::Execute(Err("timeout".to_string())),
+            ),
+            &[],
+        )
+        .unwrap();
+
+        let by_alice: LocksResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &merger_addr,
+                &QueryMsg::GetLocksByUser {
+                    user: alice.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
             .unwrap();
+        let ids: Vec<u64> = by_alice.locks.iter().map(|l| l.lock_id).collect();
+        assert_eq!(ids, vec![first, third]);
 
-        // 3) Check for expected events
-        let wasm_events: Vec<_> = res.events.iter().filter(|e| e.ty == "wasm").collect();
-        assert!(wasm_events.len() >= 2, "Expected at least 2 wasm events");
+        // pagination picks up after the given lock id
+        let page: LocksResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &merger_addr,
+                &QueryMsg::GetLocksByToken {
+                    token: token_a_addr.to_string(),
+                    start_after: Some(first),
+                    limit: Some(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(page.locks.len(), 1);
+        assert_eq!(page.locks[0].lock_id, second);
 
-        let found_lock_and_mint = wasm_events.iter().any(|ev| {
-            ev.attributes
-                .iter()
-                .any(|attr| attr.key == "action" && attr.value == "lock_and_mint")
-        });
-        assert!(
-            found_lock_and_mint,
-            "No lock_and_mint action found in events"
+        let pending: LocksResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &merger_addr,
+                &QueryMsg::GetLocksByStatus {
+                    status: LockStatus::Pending,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        let ids: Vec<u64> = pending.locks.iter().map(|l| l.lock_id).collect();
+        assert_eq!(ids, vec![first, third]);
+
+        let timed_out: LocksResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &merger_addr,
+                &QueryMsg::GetLocksByStatus {
+                    status: LockStatus::TimedOut,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(timed_out.locks.len(), 1);
+        assert_eq!(timed_out.locks[0].sender, bob.to_string());
+        assert_eq!(timed_out.locks[0].amount, Uint128::new(200));
+    }
+
+    #[test]
+    fn test_callback_only_from_note() {
+        let (mut app, merger_addr, token_a_addr, _note_addr, token_a_admin) = setup();
+        let user = "user1".into_addr();
+
+        let lock_id = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(500),
         );
 
-        let mock_note_evt = res
-            .events
-            .iter()
-            .find(|ev| ev.attributes.iter().any(|at| at.key == "mock_note"))
-            .expect("mock note should have been called");
+        let err = app
+            .execute_contract(
+                user,
+                merger_addr.clone(),
+                &mint_callback(
+                    &app,
+                    &merger_addr,
+                    lock_id,
+                    Callback::FatalError("oops".to_string()),
+                ),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
 
-        let mock_note_attr = mock_note_evt
-            .attributes
+    #[test]
+    fn test_callback_rejects_unknown_payload() {
+        let (mut app, merger_addr, token_a_addr, note_addr, token_a_admin) = setup();
+        let user = "user1".into_addr();
+        let lock_id = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(100),
+        );
+
+        // a bare lock id is not a payload the merger ever sends
+        let err = app
+            .execute_contract(
+                note_addr,
+                merger_addr.clone(),
+                &ExecuteMsg::Callback(CallbackMessage {
+                    initiator: merger_addr.clone(),
+                    initiator_msg: to_json_binary(&lock_id).unwrap(),
+                    result: Callback::Execute(Err("timeout".to_string())),
+                }),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidCallback {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        assert_eq!(
+            query_lock(&app, &merger_addr, lock_id).status,
+            LockStatus::Pending
+        );
+        assert_eq!(query_balance(&app, &token_a_addr, &user), Uint128::zero());
+    }
+
+    #[test]
+    fn test_release_from_xion_proxy() {
+        let (mut app, merger_addr, token_a_addr, note_addr, token_a_admin) = setup();
+        let user = "user1".into_addr();
+        let xion_proxy = "xion_minter_proxy".into_addr();
+        let lock_id = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(1000),
+        );
+        let success = Callback::Execute(Ok(polytone::callbacks::ExecutionResponse {
+            executed_by: "xion_proxy".to_string(),
+            result: vec![],
+        }));
+        app.execute_contract(
+            note_addr.clone(),
+            merger_addr.clone(),
+            &mint_callback(&app, &merger_addr, lock_id, success.clone()),
+            &[],
+        )
+        .unwrap();
+
+        let release = |amount: u128| {
+            ExecuteMsg::Release(ReleaseMsg {
+                recipient: user.to_string(),
+                source_token: token_a_addr.to_string(),
+                amount: Uint128::new(amount),
+            })
+        };
+
+        // nobody may release until the proxy is configured
+        let err = app
+            .execute_contract(xion_proxy.clone(), merger_addr.clone(), &release(400), &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        app.execute_contract(
+            "merger_deployer".into_addr(),
+            merger_addr.clone(),
+            &ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                xion_proxy: Some(xion_proxy.to_string()),
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(xion_proxy.clone(), merger_addr.clone(), &release(400), &[])
+            .unwrap();
+        assert_eq!(query_balance(&app, &token_a_addr, &user), Uint128::new(400));
+
+        let source: SourceTokenResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &merger_addr,
+                &QueryMsg::GetSourceToken {
+                    token: token_a_addr.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(source.total_locked, Uint128::new(600));
+        assert_eq!(source.releasable, Uint128::new(600));
+        // releasing doesn't free up room under the cap
+        assert_eq!(source.cumulative_locked, Uint128::new(1000));
+
+        // tokens of a lock still waiting on its mint can't be released
+        let in_flight = mint_and_lock(
+            &mut app,
+            &merger_addr,
+            &token_a_addr,
+            &token_a_admin,
+            &user,
+            Uint128::new(500),
+        );
+        let err = app
+            .execute_contract(xion_proxy.clone(), merger_addr.clone(), &release(700), &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InsufficientReleasable { available } => {
+                assert_eq!(available, Uint128::new(600))
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // so it can still be refunded in full
+        app.execute_contract(
+            note_addr.clone(),
+            merger_addr.clone(),
+            &mint_callback(
+                &app,
+                &merger_addr,
+                in_flight,
+                Callback::Execute(Err("timeout".to_string())),
+            ),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(query_balance(&app, &token_a_addr, &user), Uint128::new(900));
+
+        // another holder locks a token worth a tenth as much
+        let other = "user2".into_addr();
+        app.init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &other, coins(1000, "ujuno"))
+                .unwrap()
+        });
+        app.execute_contract(
+            "merger_deployer".into_addr(),
+            merger_addr.clone(),
+            &ExecuteMsg::AddSourceToken(SourceTokenMsg {
+                token: "ujuno".to_string(),
+                kind: TokenKind::Native,
+                ratio: Decimal::percent(10),
+                cap: None,
+                user_cap: None,
+                mode: MergeMode::Lock,
+                merkle_root: None,
+            }),
+            &[],
+        )
+        .unwrap();
+        let res = app
+            .execute_contract(
+                other,
+                merger_addr.clone(),
+                &ExecuteMsg::Lock {
+                    xion_meta_account: XION_RECIPIENT.to_string(),
+                    proof: None,
+                },
+                &coins(1000, "ujuno"),
+            )
+            .unwrap();
+        let lock_id: u64 = res
+            .events
             .iter()
-            .find(|at| at.key == "mock_note")
+            .flat_map(|ev| ev.attributes.iter())
+            .find(|attr| attr.key == "lock_id")
+            .unwrap()
+            .value
+            .parse()
             .unwrap();
-        assert_eq!(mock_note_attr.value, "received_execute");
+        app.execute_contract(
+            note_addr,
+            merger_addr.clone(),
+            &mint_callback(&app, &merger_addr, lock_id, success),
+            &[],
+        )
+        .unwrap();
+
+        // merged tokens minted from token a can't claim it at that ratio
+        let err = app
+            .execute_contract(
+                xion_proxy,
+                merger_addr.clone(),
+                &ExecuteMsg::Release(ReleaseMsg {
+                    recipient: user.to_string(),
+                    source_token: "ujuno".to_string(),
+                    amount: Uint128::new(400),
+                }),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InsufficientReleasable { available } => {
+                assert_eq!(available, Uint128::new(100))
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        let held = app.wrap().query_balance(&merger_addr, "ujuno").unwrap();
+        assert_eq!(held.amount, Uint128::new(1000));
     }
 }
\ No newline at end of file
@@ -1,4 +1,6 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{
+    CheckedFromRatioError, CheckedMultiplyFractionError, OverflowError, StdError, Uint128,
+};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,6 +8,15 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    MultiplyFraction(#[from] CheckedMultiplyFractionError),
+
+    #[error("{0}")]
+    DivideFraction(#[from] CheckedFromRatioError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
     #[error("Invalid Token: only accepting specified merger tokens")]
     InvalidToken {},
 
@@ -14,4 +25,118 @@ pub enum ContractError {
 
     #[error("Serialization Error")]
     SerializationError {},
+
+    #[error("Invalid Funds: send exactly one non-zero coin")]
+    InvalidFunds {},
+
+    #[error("Invalid denom: {denom}")]
+    InvalidDenom { denom: String },
+
+    #[error("Source token {token} is disabled")]
+    SourceTokenDisabled { token: String },
+
+    #[error("Source token {token} is already registered")]
+    SourceTokenExists { token: String },
+
+    #[error("Cap exceeded: only {remaining} more can be locked")]
+    CapExceeded { remaining: Uint128 },
+
+    #[error("Invalid XION recipient {recipient}: expected a {hrp} account")]
+    InvalidRecipient { recipient: String, hrp: String },
+
+    #[error("Timeout must be between {min} and {max} seconds")]
+    InvalidTimeout { min: u64, max: u64 },
+
+    #[error("Fee must be at most {max_bps} basis points")]
+    InvalidFee { max_bps: u16 },
+
+    #[error("Insufficient backing: only {available} merged tokens can be released")]
+    InsufficientReleasable { available: Uint128 },
+
+    #[error("Invalid Merkle root {root}: expected 32 hex-encoded bytes")]
+    InvalidMerkleRoot { root: String },
+
+    #[error("Locking this token requires an eligibility proof")]
+    MissingProof {},
+
+    #[error("Eligibility proof does not match the Merkle root")]
+    InvalidProof {},
+
+    #[error("Eligibility exceeded, {remaining} left to lock")]
+    EligibilityExceeded { remaining: Uint128 },
+
+    #[error("{token} is burned on merge and cannot be released")]
+    NotReleasable { token: String },
+
+    #[error("Invalid Ratio: exchange ratio must be greater than zero")]
+    InvalidRatio {},
+
+    #[error("Invalid Amount: locked amount converts to zero merged tokens")]
+    InvalidAmount {},
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+
+    #[error("Cannot migrate from {previous_contract} {previous_version}")]
+    CannotMigrate {
+        previous_contract: String,
+        previous_version: String,
+    },
+
+    #[error("An owner is required to migrate a legacy deployment")]
+    MissingOwner {},
+
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("Merger is not open for locks: {phase}")]
+    MergerNotOpen { phase: String },
+
+    #[error("Merger can only be finalized once its lock window has closed")]
+    MergerNotClosed {},
+
+    #[error("Merger has been finalized")]
+    MergerFinalized {},
+
+    #[error("Only a registered approver can approve the merger")]
+    NotApprover {},
+
+    #[error("{approver} has already approved the merger")]
+    AlreadyApproved { approver: String },
+
+    #[error("Approvers can't change once the merger is {phase}")]
+    ApproversFrozen { phase: String },
+
+    #[error("No ownership transfer is pending for this address")]
+    NotPendingOwner {},
+
+    #[error("Invalid Config: {reason}")]
+    InvalidConfig { reason: String },
+
+    #[error("Basket is full: flush it before depositing more than {max} locks")]
+    BasketFull { max: u32 },
+
+    #[error("Basket is empty")]
+    EmptyBasket {},
+
+    #[error("No locks are waiting to be batched")]
+    EmptyBatch {},
+
+    #[error("Callback payload is neither a MintCallback nor a FlushCallback")]
+    InvalidCallback {},
+
+    #[error("Lock {lock_id} not found")]
+    LockNotFound { lock_id: u64 },
+
+    #[error("Lock {lock_id} has already been settled")]
+    LockAlreadySettled { lock_id: u64 },
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 pub mod contract;
 mod error;
 pub mod helpers;
+pub mod merkle;
 pub mod msg;
 pub mod state;
 
@@ -0,0 +1,112 @@
+//! Allowlist trees for `SetMerkleRoot`. Off-chain tooling has to hash
+//! exactly like this:
+//!
+//! - leaf: `sha256(0x00 || len(address) || address || max_amount)`, with
+//!   `len` a 4-byte big-endian byte count and `max_amount` a 16-byte
+//!   big-endian integer
+//! - node: `sha256(0x01 || min(a, b) || max(a, b))`
+//!
+//! The prefixes keep a node from ever passing as a leaf, and the length
+//! keeps one address and amount from reading as another.
+use cosmwasm_std::Uint128;
+use sha2::{Digest, Sha256};
+
+pub type Hash = [u8; 32];
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Leaf for an address allowed to lock up to `max_amount`.
+pub fn leaf_hash(address: &str, max_amount: Uint128) -> Hash {
+    Sha256::new()
+        .chain_update([LEAF_PREFIX])
+        .chain_update((address.len() as u32).to_be_bytes())
+        .chain_update(address.as_bytes())
+        .chain_update(max_amount.u128().to_be_bytes())
+        .finalize()
+        .into()
+}
+
+/// Hashes a pair in sorted order, so proofs don't need to say which side
+/// each sibling is on.
+pub fn hash_pair(a: &Hash, b: &Hash) -> Hash {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    Sha256::new()
+        .chain_update([NODE_PREFIX])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+pub fn verify_proof(root: &Hash, leaf: Hash, proof: &[Hash]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf, |node, sibling| hash_pair(&node, sibling));
+    &computed == root
+}
+
+/// Every layer of the tree, leaves first and the root last. A node
+/// without a sibling moves up a layer unchanged.
+pub fn build_layers(leaves: Vec<Hash>) -> Vec<Vec<Hash>> {
+    let mut layers = vec![leaves];
+    while let Some(layer) = layers.last().filter(|layer| layer.len() > 1) {
+        let next = layer
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hash_pair(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        layers.push(next);
+    }
+    layers
+}
+
+/// Siblings of the leaf at `index`, from the bottom of the tree up.
+pub fn build_proof(layers: &[Vec<Hash>], mut index: usize) -> Vec<Hash> {
+    let mut proof = vec![];
+    for layer in &layers[..layers.len().saturating_sub(1)] {
+        if let Some(sibling) = layer.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        index /= 2;
+    }
+    proof
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proofs_verify_against_root() {
+        let leaves: Vec<Hash> = (1..=5u128)
+            .map(|i| leaf_hash(&format!("juno1user{}", i), Uint128::new(i * 100)))
+            .collect();
+        let layers = build_layers(leaves.clone());
+        let root = layers.last().unwrap()[0];
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = build_proof(&layers, index);
+            assert!(verify_proof(&root, *leaf, &proof));
+        }
+
+        // a leaf claiming a larger amount doesn't verify
+        let proof = build_proof(&layers, 0);
+        let inflated = leaf_hash("juno1user1", Uint128::new(1000));
+        assert!(!verify_proof(&root, inflated, &proof));
+
+        // the address and amount can't run into each other
+        assert_ne!(
+            leaf_hash("juno1user1", Uint128::new(23)),
+            leaf_hash("juno1user12", Uint128::new(3))
+        );
+
+        // a single leaf is its own root
+        let layers = build_layers(vec![leaves[0]]);
+        assert_eq!(layers.last().unwrap()[0], leaves[0]);
+        assert!(build_proof(&layers, 0).is_empty());
+    }
+}
@@ -1,29 +1,244 @@
-use crate::state::Config;
+use crate::state::{Config, LockStatus, MergeMode, MergerPhase, TokenKind};
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cw20::Cw20ReceiveMsg;
-use cosmwasm_std::{CosmosMsg, Empty, Uint64};
-use polytone::{
-    ack::Callback,
-    callbacks::{CallbackMessage, CallbackRequest},
-};
+use cosmwasm_std::{Decimal, Timestamp, Uint128};
+use polytone::callbacks::CallbackMessage;
+
+pub use fusogen_types::juno_merger::ReleaseMsg;
+pub use fusogen_types::polytone::PolytoneExecuteMsg;
 
 #[cw_serde]
 pub struct InstantiateMsg {
+    /// Defaults to the instantiator.
+    pub owner: Option<String>,
+    /// May pause and unpause locking alongside the owner.
+    pub guardian: Option<String>,
     pub note_contract: String,
-    pub token_a: String,
-    pub token_b: String,
+    pub source_tokens: Vec<SourceTokenMsg>,
     pub xion_mint_contract: String,
+    /// Polytone timeout for mints. Defaults to 300 seconds.
+    pub timeout_seconds: Option<u64>,
+    /// Bech32 prefix of XION accounts. Defaults to `xion`.
+    pub xion_hrp: Option<String>,
+    /// Start of the lock window. Open immediately if unset.
+    pub start_time: Option<Timestamp>,
+    /// End of the lock window. Open indefinitely if unset.
+    pub end_time: Option<Timestamp>,
+    /// Fee on each lock in basis points. Defaults to none.
+    pub fee_bps: Option<u16>,
+    /// Receives fees. Required if `fee_bps` is non-zero.
+    pub treasury: Option<String>,
+    /// Queue locks for `ProcessBatch`. Defaults to off.
+    pub batching: Option<bool>,
+    /// Governance contracts, e.g. each merging DAO's DAO DAO core, that
+    /// must all `Approve` before locks open. None are required if unset.
+    pub approvers: Option<Vec<String>>,
+}
+
+#[cw_serde]
+pub struct SourceTokenMsg {
+    /// CW20 contract address or native denom, depending on `kind`.
+    pub token: String,
+    #[serde(default)]
+    pub kind: TokenKind,
+    /// Merged tokens minted per source token locked, e.g. `"2.5"`.
+    pub ratio: Decimal,
+    pub cap: Option<Uint128>,
+    /// Per-address cap. Unlimited if unset.
+    #[serde(default)]
+    pub user_cap: Option<Uint128>,
+    /// Defaults to `lock`.
+    #[serde(default)]
+    pub mode: MergeMode,
+    /// Restricts locks to a snapshot allowlist, see `EligibilityProof`.
+    #[serde(default)]
+    pub merkle_root: Option<String>,
+}
+
+/// Shows the sender is in a source token's allowlist with `max_amount`.
+/// Leaves are `sha256(address || max_amount)` and pairs are hashed in
+/// sorted order; the `merkle` binary builds proofs from a CSV.
+#[cw_serde]
+pub struct EligibilityProof {
+    pub max_amount: Uint128,
+    /// Hex sibling hashes, from the leaf up.
+    pub proof: Vec<String>,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
+    /// Lock a whitelisted native or IBC denom sent as funds.
+    Lock {
+        xion_meta_account: String,
+        /// Required if the denom has a Merkle root.
+        #[serde(default)]
+        proof: Option<EligibilityProof>,
+    },
+    /// Like `Lock`, but queue the mint in the sender's basket.
+    Deposit {
+        xion_meta_account: String,
+        #[serde(default)]
+        proof: Option<EligibilityProof>,
+    },
+    /// Mint everything in the sender's basket with a single Polytone
+    /// packet.
+    Flush {},
+    /// Anyone: mint up to `limit` locks from the batch queue, oldest
+    /// first, with a single `BatchMint` on XION.
+    ProcessBatch {
+        limit: Option<u32>,
+    },
+    /// Polytone callback for a lock's mint on XION. Only accepted from
+    /// the configured note.
+    Callback(CallbackMessage),
+    /// Owner: register a new source token, enabled.
+    AddSourceToken(SourceTokenMsg),
+    /// Owner: stop accepting new locks of a source token.
+    DisableSourceToken {
+        token: String,
+    },
+    /// Owner: resume accepting locks of a disabled source token.
+    EnableSourceToken {
+        token: String,
+    },
+    /// Owner: replace a source token's global and per-address caps.
+    /// `None` removes a cap.
+    SetSourceTokenCaps {
+        token: String,
+        cap: Option<Uint128>,
+        user_cap: Option<Uint128>,
+    },
+    /// Owner: change whether a source token is held or burned. Applies
+    /// to new locks only.
+    SetMergeMode {
+        token: String,
+        mode: MergeMode,
+    },
+    /// Owner: replace a source token's allowlist root. `None` lets
+    /// anyone lock it.
+    SetMerkleRoot {
+        token: String,
+        root: Option<String>,
+    },
+    /// Owner: drop a source token from the registry.
+    RemoveSourceToken {
+        token: String,
+    },
+    /// xion-minter's proxy: hand back locked source tokens for merged
+    /// tokens burned on XION.
+    Release(ReleaseMsg),
+    /// Owner: change the bridge contracts or guardian.
+    UpdateConfig(UpdateConfigMsg),
+    /// Owner: propose a new owner. Replaces any pending proposal.
+    TransferOwnership {
+        new_owner: String,
+    },
+    /// Pending owner: accept a proposed ownership transfer.
+    AcceptOwnership {},
+    /// Owner or guardian: stop accepting new locks. Callbacks and
+    /// refunds for existing locks still go through.
+    Pause {
+        reason: Option<String>,
+    },
+    /// Owner or guardian: resume accepting locks.
+    Unpause {},
+    /// Approver: record this approver's approval. Locking opens once
+    /// every approver has approved.
+    Approve {},
+    /// Owner: replace the approver set. Approvers that stay keep their
+    /// approval. Only allowed until the merger opens.
+    SetApprovers {
+        approvers: Vec<String>,
+    },
+    /// Anyone: once the lock window has closed, record the final totals
+    /// and permanently stop accepting locks.
+    Finalize {},
+}
+
+/// Fields left as `None` are unchanged.
+#[cw_serde]
+#[derive(Default)]
+pub struct UpdateConfigMsg {
+    /// Rejected while any lock is still waiting on its callback.
+    pub note_contract: Option<String>,
+    pub xion_mint_contract: Option<String>,
+    pub guardian: Option<String>,
+    pub xion_proxy: Option<String>,
+    pub timeout_seconds: Option<u64>,
+    pub xion_hrp: Option<String>,
+    /// Window changes are rejected once the merger is finalized.
+    pub start_time: Option<Timestamp>,
+    pub end_time: Option<Timestamp>,
+    pub fee_bps: Option<u16>,
+    pub treasury: Option<String>,
+    /// Locks already queued stay queued when batching is turned off.
+    pub batching: Option<bool>,
+    /// Optional fields to unset. A field can't be both set and cleared.
+    #[serde(default)]
+    pub clear: Vec<OptionalConfigField>,
+}
+
+/// Config fields `UpdateConfig` can unset.
+#[cw_serde]
+pub enum OptionalConfigField {
+    Guardian,
+    XionProxy,
+    Treasury,
+    StartTime,
+    EndTime,
+}
+
+impl OptionalConfigField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OptionalConfigField::Guardian => "guardian",
+            OptionalConfigField::XionProxy => "xion_proxy",
+            OptionalConfigField::Treasury => "treasury",
+            OptionalConfigField::StartTime => "start_time",
+            OptionalConfigField::EndTime => "end_time",
+        }
+    }
+}
+
+/// Sent along with each mint as the Polytone callback message, so the
+/// acknowledgement identifies the lock it settles.
+#[cw_serde]
+pub struct MintCallback {
+    pub lock_id: u64,
+    pub sender: String,
+    pub token: String,
+    pub amount: Uint128,
+}
+
+/// Callback message for a `Flush` or `ProcessBatch`, settling every lock
+/// it sent.
+#[cw_serde]
+pub struct FlushCallback {
+    pub lock_ids: Vec<u64>,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// Owner to install when migrating a deployment that predates cw2
+    /// versioning. Ignored otherwise.
+    pub owner: Option<String>,
 }
 
 #[cw_serde]
 pub enum ReceiveMsg {
     Lock {
         xion_meta_account: String,
+        /// Required if the token has a Merkle root.
+        #[serde(default)]
+        proof: Option<EligibilityProof>,
+    },
+    /// Queue the lock in the sender's basket instead of minting right
+    /// away.
+    Deposit {
+        xion_meta_account: String,
+        #[serde(default)]
+        proof: Option<EligibilityProof>,
     },
 }
 
@@ -32,21 +247,182 @@ pub enum ReceiveMsg {
 pub enum QueryMsg {
     #[returns(Config)]
     GetConfig {},
+    #[returns(OwnershipResponse)]
+    GetOwnership {},
+    #[returns(PauseResponse)]
+    GetPauseStatus {},
+    #[returns(PhaseResponse)]
+    GetPhase {},
+    /// Every approver, in address order.
+    #[returns(ApprovalsResponse)]
+    GetApprovals {},
+    #[returns(SourceTokenResponse)]
+    GetSourceToken { token: String },
+    #[returns(SourceTokensResponse)]
+    GetSourceTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(UserCapacityResponse)]
+    GetUserCapacity { user: String, token: String },
+    #[returns(FeesCollectedResponse)]
+    GetFeesCollected {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(LockResponse)]
+    GetLock { lock_id: u64 },
+    #[returns(LocksResponse)]
+    GetLocksByUser {
+        user: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(LocksResponse)]
+    GetLocksByToken {
+        token: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Queued locks waiting for `user`'s next `Flush`.
+    #[returns(LocksResponse)]
+    GetBasket { user: String },
+    #[returns(BatchQueueResponse)]
+    GetBatchQueue {},
+    #[returns(LocksResponse)]
+    GetLocksByStatus {
+        status: LockStatus,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
 pub struct ConfigResponse {
+    pub guardian: Option<String>,
     pub note_contract: String,      
-    pub token_a: String,           
-    pub token_b: String,           
     pub xion_mint_contract: String, 
+    pub xion_proxy: Option<String>,
+    pub timeout_seconds: u64,
+    pub xion_hrp: String,
+    pub start_time: Option<Timestamp>,
+    pub end_time: Option<Timestamp>,
+    pub fee_bps: u16,
+    pub treasury: Option<String>,
+    pub batching: bool,
 }
 
 #[cw_serde]
-pub enum PolytoneExecuteMsg {
-    Execute {
-        msgs: Vec<CosmosMsg<Empty>>,
-        callback: Option<CallbackRequest>,
-        timeout_seconds: Uint64,
-    },
-}
\ No newline at end of file
+pub struct OwnershipResponse {
+    pub owner: String,
+    pub pending_owner: Option<String>,
+}
+
+#[cw_serde]
+pub struct PauseResponse {
+    pub paused: bool,
+    pub reason: Option<String>,
+}
+
+#[cw_serde]
+pub struct PhaseResponse {
+    pub phase: MergerPhase,
+    pub start_time: Option<Timestamp>,
+    pub end_time: Option<Timestamp>,
+    /// Set while pending.
+    pub seconds_until_start: Option<u64>,
+    /// Set while pending or open, if the window has an end.
+    pub seconds_until_end: Option<u64>,
+    pub finalized_at: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct Approval {
+    pub approver: String,
+    /// `None` until the approver approves.
+    pub approved_at: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct ApprovalsResponse {
+    pub approvals: Vec<Approval>,
+    /// Whether every approver has approved.
+    pub approved: bool,
+}
+
+#[cw_serde]
+pub struct SourceTokenResponse {
+    pub token: String,
+    pub kind: TokenKind,
+    pub ratio: Decimal,
+    pub cap: Option<Uint128>,
+    pub user_cap: Option<Uint128>,
+    pub mode: MergeMode,
+    pub merkle_root: Option<String>,
+    pub enabled: bool,
+    /// Currently held by the merger.
+    pub total_locked: Uint128,
+    /// Ever locked, net of refunds, and counted against `cap`.
+    pub cumulative_locked: Uint128,
+    /// Merged tokens minted against this token and not yet released.
+    pub releasable: Uint128,
+    /// Lifetime amount burned in `burn` mode.
+    pub total_burned: Uint128,
+    /// How much more can be locked before hitting `cap`. `None` if
+    /// uncapped.
+    pub remaining: Option<Uint128>,
+    /// Lifetime fees paid to the treasury in this token.
+    pub fees_collected: Uint128,
+}
+
+#[cw_serde]
+pub struct UserCapacityResponse {
+    pub user: String,
+    pub token: String,
+    /// Locked by this address, net of refunds.
+    pub locked: Uint128,
+    /// How much more this address can lock under both caps. `None` if
+    /// neither applies.
+    pub remaining: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct SourceTokensResponse {
+    pub source_tokens: Vec<SourceTokenResponse>,
+}
+
+#[cw_serde]
+pub struct LockResponse {
+    pub lock_id: u64,
+    pub sender: String,
+    pub token: String,
+    pub amount: Uint128,
+    pub fee: Uint128,
+    pub mode: MergeMode,
+    pub mint_amount: Uint128,
+    pub xion_recipient: String,
+    pub block_height: u64,
+    pub status: LockStatus,
+}
+
+#[cw_serde]
+pub struct BatchQueueResponse {
+    /// Locks waiting for `ProcessBatch`.
+    pub queued: u32,
+}
+
+#[cw_serde]
+pub struct FeeCollected {
+    pub token: String,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct FeesCollectedResponse {
+    pub fees: Vec<FeeCollected>,
+}
+
+#[cw_serde]
+pub struct LocksResponse {
+    pub locks: Vec<LockResponse>,
+}
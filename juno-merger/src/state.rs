@@ -1,14 +1,245 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::Addr;
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Decimal, Timestamp, Uint128};
+use cw_storage_plus::{Deque, Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
 //TODO - store as Addr or String?
 #[cw_serde]
 pub struct Config {
+    pub guardian: Option<Addr>,
     pub note_contract: Addr,      
-    pub token_a: Addr,           
-    pub token_b: Addr,           
     pub xion_mint_contract: String, 
+    /// xion-minter's Polytone proxy on this chain; the only caller
+    /// allowed to release locked tokens.
+    pub xion_proxy: Option<Addr>,
+    /// How long the note waits for XION before reporting a timeout.
+    pub timeout_seconds: u64,
+    /// Bech32 prefix lock recipients must carry, e.g. `xion`.
+    pub xion_hrp: String,
+    /// Locks open at this time. Open from instantiation if unset.
+    pub start_time: Option<Timestamp>,
+    /// Locks close at this time. Without one the merger stays open and
+    /// can never be finalized.
+    pub end_time: Option<Timestamp>,
+    /// Share of each lock, in basis points, paid to `treasury` once its
+    /// mint succeeds.
+    pub fee_bps: u16,
+    /// Required whenever `fee_bps` is non-zero.
+    pub treasury: Option<Addr>,
+    /// Queue locks for `ProcessBatch` instead of sending a packet per lock.
+    pub batching: bool,
 }
 
-pub const CONFIG: Item<Config> = Item::new("config");
\ No newline at end of file
+/// Where the merger is in its lock window.
+#[cw_serde]
+pub enum MergerPhase {
+    /// Before `start_time`.
+    Pending,
+    /// Within the lock window, but still missing approvals.
+    AwaitingApproval,
+    /// Accepting locks.
+    Open,
+    /// Past `end_time`, waiting for someone to finalize.
+    Closed,
+    /// Finalized; locking is permanently disabled.
+    Finalized,
+}
+
+impl MergerPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MergerPhase::Pending => "pending",
+            MergerPhase::AwaitingApproval => "awaiting_approval",
+            MergerPhase::Open => "open",
+            MergerPhase::Closed => "closed",
+            MergerPhase::Finalized => "finalized",
+        }
+    }
+}
+
+/// Recorded once the merger is finalized.
+#[cw_serde]
+pub struct Finalization {
+    pub time: Timestamp,
+    pub lock_count: u64,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum TokenKind {
+    /// A CW20 contract, locked via `Send`.
+    #[default]
+    Cw20,
+    /// A native, token-factory or IBC denom, locked via `ExecuteMsg::Lock`.
+    Native,
+}
+
+/// What happens to a source token once it has been merged.
+#[cw_serde]
+#[derive(Default)]
+pub enum MergeMode {
+    /// Held by the merger, and releasable for merged tokens burned on XION.
+    #[default]
+    Lock,
+    /// Burned once its mint succeeds on XION. With `immediate` it is
+    /// burned as soon as it is received instead, so a failed or timed out
+    /// mint can no longer be refunded.
+    Burn { immediate: bool },
+}
+
+/// The owner may update the config and manage the source token
+/// registry. Ownership moves in two steps: the owner proposes a new
+/// owner, who must then accept.
+#[cw_serde]
+pub struct Ownership {
+    pub owner: Addr,
+    pub pending_owner: Option<Addr>,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct PauseState {
+    pub paused: bool,
+    pub reason: Option<String>,
+}
+
+/// A token the merger accepts, and how it converts into the merged token.
+#[cw_serde]
+pub struct SourceTokenConfig {
+    pub kind: TokenKind,
+    /// Merged tokens minted per source token locked.
+    pub ratio: Decimal,
+    /// Maximum amount of this token that may ever be locked, net of
+    /// refunds.
+    pub cap: Option<Uint128>,
+    /// Maximum amount of this token any one address may have locked.
+    #[serde(default)]
+    pub user_cap: Option<Uint128>,
+    #[serde(default)]
+    pub mode: MergeMode,
+    /// Hex sha256 Merkle root of the addresses allowed to lock this token
+    /// and how much each may lock. Anyone may lock if unset.
+    #[serde(default)]
+    pub merkle_root: Option<String>,
+    pub enabled: bool,
+}
+
+/// Outcome of the cross-chain mint for a lock, as reported by the
+/// Polytone callback.
+#[cw_serde]
+pub enum LockStatus {
+    /// Waiting in the sender's basket for a `Flush`, or in the batch
+    /// queue for a `ProcessBatch`.
+    Queued,
+    /// Sent to the note, no callback received yet.
+    Pending,
+    /// The mint executed on XION.
+    Minted,
+    /// The mint errored on XION and the tokens were refunded.
+    Failed,
+    /// The packet timed out before it was relayed and the tokens were
+    /// refunded.
+    TimedOut,
+}
+
+impl LockStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LockStatus::Queued => "queued",
+            LockStatus::Pending => "pending",
+            LockStatus::Minted => "minted",
+            LockStatus::Failed => "failed",
+            LockStatus::TimedOut => "timed_out",
+        }
+    }
+}
+
+#[cw_serde]
+pub struct Lock {
+    pub sender: Addr,
+    /// CW20 contract address or native denom.
+    pub token: String,
+    pub kind: TokenKind,
+    pub amount: Uint128,
+    /// Part of `amount` owed to the treasury if the mint succeeds.
+    #[serde(default)]
+    pub fee: Uint128,
+    /// The token's merge mode when it was locked.
+    #[serde(default)]
+    pub mode: MergeMode,
+    /// Merged tokens requested on XION for `amount - fee` after applying
+    /// the exchange ratio.
+    pub mint_amount: Uint128,
+    pub xion_recipient: String,
+    pub block_height: u64,
+    pub status: LockStatus,
+}
+
+pub struct LockIndexes<'a> {
+    pub sender: MultiIndex<'a, Addr, Lock, u64>,
+    pub token: MultiIndex<'a, String, Lock, u64>,
+    pub status: MultiIndex<'a, String, Lock, u64>,
+}
+
+impl<'a> IndexList<Lock> for LockIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Lock>> + '_> {
+        let v: Vec<&dyn Index<Lock>> = vec![&self.sender, &self.token, &self.status];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Config layout of deployments that predate cw2 versioning, when the
+/// merger only knew about two CW20s.
+#[cw_serde]
+pub struct LegacyConfig {
+    pub note_contract: Addr,
+    pub token_a: Addr,
+    pub token_b: Addr,
+    pub xion_mint_contract: String,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+pub const OWNERSHIP: Item<Ownership> = Item::new("ownership");
+pub const PAUSE: Item<PauseState> = Item::new("pause");
+pub const FINALIZATION: Item<Finalization> = Item::new("finalization");
+/// Governance contracts that must all approve before locks open, with
+/// when each one did.
+pub const APPROVERS: Map<&Addr, Option<Timestamp>> = Map::new("approvers");
+
+/// Keyed by CW20 contract address or native denom.
+pub const SOURCE_TOKENS: Map<&str, SourceTokenConfig> = Map::new("source_tokens");
+/// Amount of each source token the merger currently holds.
+pub const TOTAL_LOCKED: Map<&str, Uint128> = Map::new("total_locked");
+/// Amount of each source token ever locked, net of refunds; what `cap`
+/// is checked against, so tokens leaving the merger don't free up room.
+pub const CUMULATIVE_LOCKED: Map<&str, Uint128> = Map::new("cumulative_locked");
+/// Merged tokens minted against each source token and not yet released;
+/// the most `Release` will hand back of it.
+pub const RELEASABLE: Map<&str, Uint128> = Map::new("releasable");
+/// Lifetime amount of each source token burned by the merger.
+pub const TOTAL_BURNED: Map<&str, Uint128> = Map::new("total_burned");
+/// Lifetime fees paid to the treasury, per source token.
+pub const FEES_COLLECTED: Map<&str, Uint128> = Map::new("fees_collected");
+/// Amount of each source token locked by each address, net of refunds.
+pub const USER_LOCKED: Map<(&Addr, &str), Uint128> = Map::new("user_locked");
+
+pub const LOCK_COUNT: Item<u64> = Item::new("lock_count");
+/// Locks waiting for `ProcessBatch`, oldest first.
+pub const BATCH_QUEUE: Deque<u64> = Deque::new("batch_queue");
+/// Ids of each address's queued locks, sent together on `Flush`.
+pub const BASKETS: Map<&Addr, Vec<u64>> = Map::new("baskets");
+
+/// Every lock ever made, keyed by lock id and indexed by sender, token
+/// and status.
+pub fn locks<'a>() -> IndexedMap<'a, u64, Lock, LockIndexes<'a>> {
+    let indexes = LockIndexes {
+        sender: MultiIndex::new(|_pk, lock| lock.sender.clone(), "locks", "locks__sender"),
+        token: MultiIndex::new(|_pk, lock| lock.token.clone(), "locks", "locks__token"),
+        status: MultiIndex::new(
+            |_pk, lock| lock.status.as_str().to_string(),
+            "locks",
+            "locks__status",
+        ),
+    };
+    IndexedMap::new("locks", indexes)
+}
\ No newline at end of file
@@ -1,23 +1,41 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{
-    entry_point, to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response,
-    StdResult, SubMsg, Uint128, WasmMsg,
+    entry_point, from_json, to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order,
+    Reply, Response, StdResult, Storage, SubMsg, Timestamp, Uint128, Uint64, WasmMsg,
 };
 
-//use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw20;
+use cw20::Cw20ReceiveMsg;
 use cw20_base;
+use fusogen_types::juno_merger::{ReleaseMsg, RemoteExecuteMsg};
+use fusogen_types::polytone::PolytoneExecuteMsg;
+use polytone::callbacks::{Callback, CallbackMessage, CallbackRequest};
+use semver::Version;
 
 use crate::error::ContractError;
-use crate::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{Config, CONFIG};
+use crate::msg::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, MintEntry, MintLimitsResponse,
+    NonceResponse, PauseResponse, QueryMsg, ReceiveMsg, RedemptionResponse, SenderInfo,
+    UpdateConfigMsg, VestingResponse, VoiceQueryMsg,
+};
+use crate::state::{
+    Config, MintRecord, PauseState, RateLimit, Redemption, RedemptionStatus, Vesting,
+    VestingSchedule, CONFIG, LEGACY_CONFIG, MINT_WINDOW, PAUSE, PROCESSED_NONCES, REDEMPTIONS,
+    REDEMPTION_COUNT, VESTINGS, VESTING_COUNT, VESTING_SCHEDULES,
+};
 
 //version info for migration info
-//const CONTRACT_NAME: &str = "crates.io:xion-minter";
-//const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const CONTRACT_NAME: &str = "crates.io:xion-minter";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
 
+// how long the note waits for Juno before reporting a timeout
+const NOTE_TIMEOUT_SECONDS: u64 = 300;
+
+const JUNO_HRP: &str = "juno";
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -25,7 +43,16 @@ pub fn instantiate(
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    //set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let guardian = match msg.guardian {
+        Some(guardian) => deps.api.addr_validate(&guardian)?,
+        None => info.sender.clone(),
+    };
+    let voice = deps.api.addr_validate(&msg.polytone_voice)?;
+    if let Some(rate_limit) = &msg.rate_limit {
+        validate_rate_limit(rate_limit)?;
+    }
 
     //this calls a separate contract hence why you have to make
     //a separate InstantiateMsg call
@@ -37,7 +64,7 @@ pub fn instantiate(
         initial_balances: vec![],
         mint: Some(cw20::MinterResponse {
             minter: env.contract.address.to_string(),
-            cap: None,
+            cap: msg.supply_cap,
         }),
         marketing: None,
     };
@@ -56,15 +83,89 @@ pub fn instantiate(
     CONFIG.save(
         deps.storage,
         &Config {
+            guardian: guardian.clone(),
+            voice: voice.clone(),
+            juno_merger: None,
             minter: None,
             token_contract: None,
+            supply_cap: msg.supply_cap,
+            rate_limit: msg.rate_limit,
+            note: None,
         },
     )?;
 
     Ok(Response::new()
         .add_submessage(instantiate_token_submsg)
         .add_attribute("action", "instantiate")
-        .add_attribute("minter", info.sender))
+        .add_attribute("minter", info.sender)
+        .add_attribute("guardian", guardian)
+        .add_attribute("voice", voice))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous_version = match get_contract_version(deps.storage) {
+        Ok(previous) => {
+            if previous.contract != CONTRACT_NAME {
+                return Err(ContractError::CannotMigrate {
+                    previous_contract: previous.contract,
+                    previous_version: previous.version,
+                });
+            }
+            let stored: Version = previous.version.parse()?;
+            let new: Version = CONTRACT_VERSION.parse()?;
+            if stored > new {
+                return Err(ContractError::CannotMigrate {
+                    previous_contract: previous.contract,
+                    previous_version: previous.version,
+                });
+            }
+            previous.version
+        }
+        Err(_) => {
+            // deployments made before cw2 versioning have no guardian or
+            // voice; the token carries over, but the first-come minter was
+            // never checked and has to be bound again through BindProxy
+            let legacy = LEGACY_CONFIG.load(deps.storage)?;
+            let guardian = msg
+                .guardian
+                .ok_or_else(|| missing_migrate_field("guardian"))?;
+            let voice = msg
+                .polytone_voice
+                .ok_or_else(|| missing_migrate_field("polytone_voice"))?;
+            if let Some(juno_merger) = &msg.juno_merger {
+                validate_juno_merger(juno_merger)?;
+            }
+            CONFIG.save(
+                deps.storage,
+                &Config {
+                    guardian: deps.api.addr_validate(&guardian)?,
+                    voice: deps.api.addr_validate(&voice)?,
+                    juno_merger: msg.juno_merger,
+                    token_contract: legacy.token_contract,
+                    minter: None,
+                    // legacy tokens were instantiated without a CW20 cap
+                    supply_cap: None,
+                    rate_limit: None,
+                    note: None,
+                },
+            )?;
+            "legacy".to_string()
+        }
+    };
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("previous_version", previous_version)
+        .add_attribute("version", CONTRACT_VERSION))
+}
+
+fn missing_migrate_field(field: &str) -> ContractError {
+    ContractError::MissingMigrateField {
+        field: field.to_string(),
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -76,7 +177,7 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 }
 
 fn handle_instantiate_token_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
-    if let Some(res) = msg.result.into_result().ok() {
+    if let Ok(res) = msg.result.into_result() {
         let contract_address = res
             .events
             .iter()
@@ -87,7 +188,7 @@ fn handle_instantiate_token_reply(deps: DepsMut, msg: Reply) -> Result<Response,
                     .find(|attr| attr.key == "_contract_address")
             })
             .map(|attr| attr.value.clone())
-            .ok_or_else(|| ContractError::NoContractAddress {})?;
+            .ok_or(ContractError::NoContractAddress {})?;
 
         let validated_addr = deps.api.addr_validate(&contract_address)?;
         let mut config = CONFIG.load(deps.storage)?;
@@ -112,117 +213,931 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Mint { amount, recipient } => mint_tokens(deps, env, info, amount, recipient),
+        ExecuteMsg::Mint {
+            amount,
+            recipient,
+            nonce,
+            source_token,
+        } => mint_tokens(deps, env, info, amount, recipient, nonce, source_token),
+        ExecuteMsg::BatchMint { entries } => batch_mint(deps, env, info, entries),
+        ExecuteMsg::Pause { reason } => set_paused(deps, info, true, reason),
+        ExecuteMsg::Unpause {} => set_paused(deps, info, false, None),
+        ExecuteMsg::BindProxy { proxy, juno_merger } => bind_proxy(deps, info, proxy, juno_merger),
+        ExecuteMsg::UpdateRateLimit { rate_limit } => update_rate_limit(deps, info, rate_limit),
+        ExecuteMsg::UpdateConfig(update) => update_config(deps, info, update),
+        ExecuteMsg::SetVestingSchedule {
+            source_token,
+            schedule,
+        } => set_vesting_schedule(deps, info, source_token, schedule),
+        ExecuteMsg::Claim {} => claim(deps, env, info),
+        ExecuteMsg::Receive(wrapper) => receive_cw20(deps, env, info, wrapper),
+        ExecuteMsg::Callback(callback) => handle_callback(deps, env, info, callback),
     }
 }
 
-fn mint_tokens(
+fn update_config(
     deps: DepsMut,
-    _env: Env,
     info: MessageInfo,
-    amount: Uint128,
-    recipient: Option<String>,
+    update: UpdateConfigMsg,
 ) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.guardian {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut response = Response::new().add_attribute("action", "update_config");
+    if let Some(note) = update.polytone_note {
+        config.note = Some(deps.api.addr_validate(&note)?);
+        response = response.add_attribute("note", note);
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+    Ok(response)
+}
+
+fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.token_contract.as_ref() != Some(&info.sender) {
+        return Err(ContractError::InvalidToken {});
+    }
 
-    match config.minter {
-        None => {
-            // First mint attempt - this address becomes the permanent minter
-            config.minter = Some(info.sender.clone());
-            CONFIG.save(deps.storage, &config.clone())?;
+    match from_json(&wrapper.msg)? {
+        ReceiveMsg::Redeem {
+            juno_recipient,
+            source_token,
+        } => {
+            let sender = deps.api.addr_validate(&wrapper.sender)?;
+            redeem(
+                deps,
+                env,
+                config,
+                sender,
+                wrapper.amount,
+                juno_recipient,
+                source_token,
+            )
         }
-        Some(minter) => {
-            // Minter is already set - verify sender has minting rights
-            if info.sender != minter {
-                return Err(ContractError::Unauthorized {});
+    }
+}
+
+/// Burns redeemed merged tokens and asks juno-merger, through Polytone, to
+/// release the matching source tokens. The redemption id rides along as
+/// the callback message so a failed release can be minted back.
+fn redeem(
+    deps: DepsMut,
+    env: Env,
+    config: Config,
+    sender: Addr,
+    amount: Uint128,
+    juno_recipient: String,
+    source_token: String,
+) -> Result<Response, ContractError> {
+    if PAUSE.may_load(deps.storage)?.unwrap_or_default().paused {
+        return Err(ContractError::Paused {});
+    }
+    if amount.is_zero() {
+        return Err(ContractError::InvalidAmount {});
+    }
+    // Juno addresses can't be validated against this chain's prefix
+    if juno_recipient.is_empty() || juno_recipient.chars().any(char::is_whitespace) {
+        return Err(ContractError::InvalidRecipient {
+            recipient: juno_recipient,
+        });
+    }
+
+    let (note, juno_merger) = match (&config.note, &config.juno_merger) {
+        (Some(note), Some(juno_merger)) => (note, juno_merger),
+        _ => return Err(ContractError::ReverseBridgeNotConfigured {}),
+    };
+    let token_addr = config
+        .token_contract
+        .as_ref()
+        .ok_or(ContractError::NoContractAddress {})?;
+
+    let redemption_id = REDEMPTION_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    REDEMPTION_COUNT.save(deps.storage, &redemption_id)?;
+    REDEMPTIONS.save(
+        deps.storage,
+        redemption_id,
+        &Redemption {
+            sender: sender.clone(),
+            amount,
+            juno_recipient: juno_recipient.clone(),
+            source_token: source_token.clone(),
+            status: RedemptionStatus::Pending,
+        },
+    )?;
+
+    let burn_msg = WasmMsg::Execute {
+        contract_addr: token_addr.to_string(),
+        msg: to_json_binary(&cw20::Cw20ExecuteMsg::Burn { amount })?,
+        funds: vec![],
+    };
+
+    let release_msg = WasmMsg::Execute {
+        contract_addr: juno_merger.clone(),
+        msg: to_json_binary(&RemoteExecuteMsg::Release(ReleaseMsg {
+            recipient: juno_recipient.clone(),
+            source_token: source_token.clone(),
+            amount,
+        }))?,
+        funds: vec![],
+    };
+    let note_msg = WasmMsg::Execute {
+        contract_addr: note.to_string(),
+        msg: to_json_binary(&PolytoneExecuteMsg::Execute {
+            msgs: vec![release_msg.into()],
+            callback: Some(CallbackRequest {
+                receiver: env.contract.address.to_string(),
+                msg: to_json_binary(&redemption_id)?,
+            }),
+            timeout_seconds: Uint64::new(NOTE_TIMEOUT_SECONDS),
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_message(burn_msg)
+        .add_message(note_msg)
+        .add_attribute("action", "redeem")
+        .add_attribute("redemption_id", redemption_id.to_string())
+        .add_attribute("sender", sender)
+        .add_attribute("amount_burned", amount)
+        .add_attribute("juno_recipient", juno_recipient)
+        .add_attribute("source_token", source_token))
+}
+
+/// Records the outcome of a redemption's release on Juno and mints the
+/// burned tokens back to the sender if it failed or timed out.
+fn handle_callback(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    callback: CallbackMessage,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.note.as_ref() != Some(&info.sender) || callback.initiator != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let redemption_id: u64 = from_json(&callback.initiator_msg)?;
+    let mut redemption = REDEMPTIONS
+        .may_load(deps.storage, redemption_id)?
+        .ok_or(ContractError::RedemptionNotFound { redemption_id })?;
+    if redemption.status != RedemptionStatus::Pending {
+        return Err(ContractError::RedemptionAlreadySettled { redemption_id });
+    }
+
+    redemption.status = match callback.result {
+        Callback::Execute(Ok(_)) => RedemptionStatus::Released,
+        _ => RedemptionStatus::Refunded,
+    };
+    REDEMPTIONS.save(deps.storage, redemption_id, &redemption)?;
+
+    let mut response = Response::new()
+        .add_attribute("action", "redeem_callback")
+        .add_attribute("redemption_id", redemption_id.to_string());
+
+    // nothing was released on Juno, so restore the burned tokens; this
+    // bypasses the rate limit as it only returns supply that was burned
+    if redemption.status == RedemptionStatus::Refunded {
+        let token_addr = config
+            .token_contract
+            .ok_or(ContractError::NoContractAddress {})?;
+        response = response
+            .add_message(WasmMsg::Execute {
+                contract_addr: token_addr.to_string(),
+                msg: to_json_binary(&cw20::Cw20ExecuteMsg::Mint {
+                    recipient: redemption.sender.to_string(),
+                    amount: redemption.amount,
+                })?,
+                funds: vec![],
+            })
+            .add_attribute("status", "refunded")
+            .add_attribute("refund_recipient", redemption.sender)
+            .add_attribute("refund_amount", redemption.amount);
+    } else {
+        response = response.add_attribute("status", "released");
+    }
+
+    Ok(response)
+}
+
+fn update_rate_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    rate_limit: Option<RateLimit>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.guardian {
+        return Err(ContractError::Unauthorized {});
+    }
+    if let Some(rate_limit) = &rate_limit {
+        validate_rate_limit(rate_limit)?;
+    }
+
+    let mut response = Response::new().add_attribute("action", "update_rate_limit");
+    if let Some(rate_limit) = &rate_limit {
+        response = response
+            .add_attribute("max_amount", rate_limit.max_amount)
+            .add_attribute("window_seconds", rate_limit.window_seconds.to_string());
+    }
+
+    config.rate_limit = rate_limit;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(response)
+}
+
+fn set_vesting_schedule(
+    deps: DepsMut,
+    info: MessageInfo,
+    source_token: String,
+    schedule: Option<VestingSchedule>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.guardian {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut response = Response::new()
+        .add_attribute("action", "set_vesting_schedule")
+        .add_attribute("source_token", &source_token);
+    match schedule {
+        Some(schedule) => {
+            if schedule.duration_seconds == 0 || schedule.cliff_seconds > schedule.duration_seconds
+            {
+                return Err(ContractError::InvalidVestingSchedule {});
             }
+            response = response
+                .add_attribute("cliff_seconds", schedule.cliff_seconds.to_string())
+                .add_attribute("duration_seconds", schedule.duration_seconds.to_string());
+            VESTING_SCHEDULES.save(deps.storage, &source_token, &schedule)?;
         }
+        // mints already vesting keep the schedule they were made under
+        None => VESTING_SCHEDULES.remove(deps.storage, &source_token),
     }
 
+    Ok(response)
+}
+
+/// Pays out everything vested across the sender's vesting mints.
+fn claim(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
     let token_addr = config
         .token_contract
         .ok_or(ContractError::NoContractAddress {})?;
 
+    let vestings = VESTINGS
+        .prefix(&info.sender)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut claimed = Uint128::zero();
+    for (id, mut vesting) in vestings {
+        let claimable = vesting.vested(env.block.time) - vesting.claimed;
+        if claimable.is_zero() {
+            continue;
+        }
+        claimed += claimable;
+        vesting.claimed += claimable;
+        if vesting.claimed == vesting.total {
+            VESTINGS.remove(deps.storage, (&info.sender, id));
+        } else {
+            VESTINGS.save(deps.storage, (&info.sender, id), &vesting)?;
+        }
+    }
+    if claimed.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let transfer = WasmMsg::Execute {
+        contract_addr: token_addr.to_string(),
+        msg: to_json_binary(&cw20::Cw20ExecuteMsg::Transfer {
+            recipient: info.sender.to_string(),
+            amount: claimed,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_message(transfer)
+        .add_attribute("action", "claim")
+        .add_attribute("recipient", info.sender)
+        .add_attribute("amount", claimed))
+}
+
+fn validate_rate_limit(rate_limit: &RateLimit) -> Result<(), ContractError> {
+    if rate_limit.max_amount.is_zero() || rate_limit.window_seconds == 0 {
+        return Err(ContractError::InvalidRateLimit {});
+    }
+    Ok(())
+}
+
+fn total_supply(deps: Deps, token: &Addr) -> StdResult<Uint128> {
+    let info: cw20::TokenInfoResponse = deps
+        .querier
+        .query_wasm_smart(token, &cw20::Cw20QueryMsg::TokenInfo {})?;
+    Ok(info.total_supply)
+}
+
+/// Sum of the mints made in the window ending at `now`.
+fn window_usage(
+    storage: &dyn Storage,
+    now: Timestamp,
+    rate_limit: &RateLimit,
+) -> StdResult<Uint128> {
+    let start = now.seconds().saturating_sub(rate_limit.window_seconds);
+    MINT_WINDOW
+        .iter(storage)?
+        .try_fold(Uint128::zero(), |used, record| {
+            let record = record?;
+            Ok(if record.time.seconds() > start {
+                used + record.amount
+            } else {
+                used
+            })
+        })
+}
+
+fn record_mint(
+    storage: &mut dyn Storage,
+    now: Timestamp,
+    rate_limit: &RateLimit,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    // drop mints that have aged out of the window
+    let start = now.seconds().saturating_sub(rate_limit.window_seconds);
+    while let Some(record) = MINT_WINDOW.front(storage)? {
+        if record.time.seconds() > start {
+            break;
+        }
+        MINT_WINDOW.pop_front(storage)?;
+    }
+
+    let used = window_usage(storage, now, rate_limit)?;
+    let remaining = rate_limit.max_amount.saturating_sub(used);
+    if amount > remaining {
+        return Err(ContractError::RateLimitExceeded { remaining });
+    }
+
+    MINT_WINDOW.push_back(storage, &MintRecord { time: now, amount })?;
+    Ok(())
+}
+
+fn bind_proxy(
+    deps: DepsMut,
+    info: MessageInfo,
+    proxy: String,
+    juno_merger: String,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.guardian {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    validate_juno_merger(&juno_merger)?;
+    let proxy = deps.api.addr_validate(&proxy)?;
+    assert_voice_proxy(deps.as_ref(), &config, &proxy, &juno_merger)?;
+
+    config.minter = Some(proxy.clone());
+    config.juno_merger = Some(juno_merger.clone());
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "bind_proxy")
+        .add_attribute("proxy", proxy)
+        .add_attribute("juno_merger", juno_merger))
+}
+
+/// Polytone proxies are instantiated by the voice, so a contract created by
+/// anyone else cannot be speaking for a remote account. The voice also
+/// records which remote account each proxy executes for, and that has to
+/// be `juno_merger`.
+fn assert_voice_proxy(
+    deps: Deps,
+    config: &Config,
+    proxy: &Addr,
+    juno_merger: &str,
+) -> Result<(), ContractError> {
+    let invalid = || ContractError::InvalidProxy {
+        proxy: proxy.to_string(),
+    };
+    let info = deps
+        .querier
+        .query_wasm_contract_info(proxy)
+        .map_err(|_| invalid())?;
+    if info.creator != config.voice.as_str() {
+        return Err(invalid());
+    }
+
+    let sender: Option<SenderInfo> = deps.querier.query_wasm_smart(
+        &config.voice,
+        &VoiceQueryMsg::SenderInfoForProxy {
+            proxy: proxy.to_string(),
+        },
+    )?;
+    match sender {
+        Some(sender) if sender.remote_sender == juno_merger => Ok(()),
+        _ => Err(ContractError::ProxySenderMismatch {
+            proxy: proxy.to_string(),
+            juno_merger: juno_merger.to_string(),
+        }),
+    }
+}
+
+/// juno-merger is a contract on Juno, so anything but a lowercase bech32
+/// address with the `juno` prefix and a 32-byte payload can't be it.
+fn validate_juno_merger(juno_merger: &str) -> Result<(), ContractError> {
+    let invalid = || ContractError::InvalidJunoMerger {
+        address: juno_merger.to_string(),
+    };
+    if juno_merger != juno_merger.to_lowercase() {
+        return Err(invalid());
+    }
+    let (hrp, data) = bech32::decode(juno_merger).map_err(|_| invalid())?;
+    if hrp.as_str() != JUNO_HRP || data.len() != 32 {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+fn set_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    paused: bool,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.guardian {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    PAUSE.save(
+        deps.storage,
+        &PauseState {
+            paused,
+            reason: reason.clone(),
+        },
+    )?;
+
+    let action = if paused { "pause" } else { "unpause" };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("reason", reason.unwrap_or_default()))
+}
+
+fn mint_tokens(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    recipient: Option<String>,
+    nonce: u64,
+    source_token: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let token_addr = assert_minter(deps.as_ref(), &config, &info)?;
+
+    let entry = MintEntry {
+        amount,
+        recipient: recipient.unwrap_or_else(|| info.sender.to_string()),
+        nonce,
+        source_token,
+    };
+    let (mint_msg, final_recipient, vesting) =
+        mint_entry(deps, &env, &config, &token_addr, entry, Uint128::zero())?;
+
+    Ok(Response::new()
+        .add_message(mint_msg)
+        .add_attribute("action", "mint_tokens")
+        .add_attribute("sender", info.sender.to_string())
+        .add_attribute("final_recipient", final_recipient)
+        .add_attribute("amount", amount)
+        .add_attribute("nonce", nonce.to_string())
+        .add_attribute("vesting", vesting.to_string()))
+}
+
+/// Mints every entry or, if any entry fails its checks, none of them.
+fn batch_mint(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    entries: Vec<MintEntry>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let token_addr = assert_minter(deps.as_ref(), &config, &info)?;
+    if entries.is_empty() {
+        return Err(ContractError::EmptyBatch {});
+    }
+
+    let count = entries.len();
+    let mut batch_amount = Uint128::zero();
+    let mut response = Response::new()
+        .add_attribute("action", "batch_mint")
+        .add_attribute("sender", info.sender.to_string());
+    for (index, entry) in entries.into_iter().enumerate() {
+        let amount = entry.amount;
+        let nonce = entry.nonce;
+        let (mint_msg, _, _) = mint_entry(
+            deps.branch(),
+            &env,
+            &config,
+            &token_addr,
+            entry,
+            batch_amount,
+        )
+        .map_err(|err| ContractError::InvalidBatchEntry {
+            index: index as u32,
+            reason: err.to_string(),
+        })?;
+        batch_amount += amount;
+        response = response
+            .add_message(mint_msg)
+            .add_attribute("nonce", nonce.to_string());
+    }
+
+    Ok(response
+        .add_attribute("count", count.to_string())
+        .add_attribute("amount", batch_amount))
+}
+
+/// Checks minting is live and the caller is the bound proxy, returning the
+/// merged token.
+fn assert_minter(deps: Deps, config: &Config, info: &MessageInfo) -> Result<Addr, ContractError> {
+    if PAUSE.may_load(deps.storage)?.unwrap_or_default().paused {
+        return Err(ContractError::Paused {});
+    }
+
+    let minter = config
+        .minter
+        .as_ref()
+        .ok_or(ContractError::ProxyNotBound {})?;
+    if info.sender != minter {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config
+        .token_contract
+        .clone()
+        .ok_or(ContractError::NoContractAddress {})
+}
+
+/// Checks and records a single mint, returning the CW20 mint, the
+/// recipient and whether the mint vests. `pending` is what earlier entries
+/// of the same batch will mint, which the supply cap has to count too.
+fn mint_entry(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    token_addr: &Addr,
+    entry: MintEntry,
+    pending: Uint128,
+) -> Result<(WasmMsg, Addr, bool), ContractError> {
+    let MintEntry {
+        amount,
+        recipient,
+        nonce,
+        source_token,
+    } = entry;
+
+    // nonces are scoped to the juno-merger the proxy speaks for; a legacy
+    // deployment must be re-bound before it can mint again
+    let juno_merger = config
+        .juno_merger
+        .as_deref()
+        .ok_or(ContractError::ProxyNotBound {})?;
+    if PROCESSED_NONCES.has(deps.storage, (juno_merger, nonce)) {
+        return Err(ContractError::NonceAlreadyProcessed {
+            juno_merger: juno_merger.to_string(),
+            nonce,
+        });
+    }
+    PROCESSED_NONCES.save(deps.storage, (juno_merger, nonce), &env.block.height)?;
+
     if amount.is_zero() {
         return Err(ContractError::InvalidAmount {});
     }
 
-    let final_recipient = match recipient {
-        Some(addr) => deps.api.addr_validate(&addr)?,
-        None => deps.api.addr_validate(&info.sender.to_string())?,
+    if let Some(cap) = config.supply_cap {
+        let supply = total_supply(deps.as_ref(), token_addr)? + pending;
+        let remaining = cap.saturating_sub(supply);
+        if amount > remaining {
+            return Err(ContractError::SupplyCapExceeded { remaining });
+        }
+    }
+    if let Some(rate_limit) = &config.rate_limit {
+        record_mint(deps.storage, env.block.time, rate_limit, amount)?;
+    }
+
+    let final_recipient = deps.api.addr_validate(&recipient)?;
+
+    // vesting mints are held here until the recipient claims them
+    let schedule = source_token
+        .as_deref()
+        .map(|token| VESTING_SCHEDULES.may_load(deps.storage, token))
+        .transpose()?
+        .flatten();
+    let vesting = schedule.is_some();
+    let mint_to = match (schedule, source_token) {
+        (Some(schedule), Some(source_token)) => {
+            let id = VESTING_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+            VESTING_COUNT.save(deps.storage, &id)?;
+            VESTINGS.save(
+                deps.storage,
+                (&final_recipient, id),
+                &Vesting {
+                    source_token,
+                    total: amount,
+                    claimed: Uint128::zero(),
+                    start: env.block.time,
+                    schedule,
+                },
+            )?;
+            env.contract.address.clone()
+        }
+        _ => final_recipient.clone(),
     };
 
     let cw20_mint_msg = cw20::Cw20ExecuteMsg::Mint {
-        recipient: final_recipient.to_string(),
+        recipient: mint_to.to_string(),
         amount,
     };
 
-    let wasm_msg = cosmwasm_std::WasmMsg::Execute {
+    let wasm_msg = WasmMsg::Execute {
         contract_addr: token_addr.to_string(),
         msg: to_json_binary(&cw20_mint_msg)?,
         funds: vec![],
     };
 
-    Ok(Response::new()
-        .add_message(cosmwasm_std::CosmosMsg::Wasm(wasm_msg))
-        .add_attribute("action", "mint_tokens")
-        .add_attribute("sender", info.sender.to_string())
-        .add_attribute("final_recipient", final_recipient)
-        .add_attribute("amount", amount))
+    Ok((wasm_msg, final_recipient, vesting))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetConfig {} => {
             let config = CONFIG.load(deps.storage)?;
             to_json_binary(&ConfigResponse {
+                guardian: config.guardian.into_string(),
+                voice: config.voice.into_string(),
+                juno_merger: config.juno_merger,
                 minter: config.minter.map(|a| a.into_string()),
                 token_contract: config.token_contract.map(|a| a.into_string()),
+                note: config.note.map(|a| a.into_string()),
+            })
+        }
+        QueryMsg::GetMintLimits {} => to_json_binary(&query_mint_limits(deps, env)?),
+        QueryMsg::GetNonce { juno_merger, nonce } => {
+            let height = PROCESSED_NONCES.may_load(deps.storage, (&juno_merger, nonce))?;
+            to_json_binary(&NonceResponse {
+                processed: height.is_some(),
+                height,
             })
         }
+        QueryMsg::GetRedemption { redemption_id } => {
+            let redemption = REDEMPTIONS.load(deps.storage, redemption_id)?;
+            to_json_binary(&RedemptionResponse {
+                redemption_id,
+                sender: redemption.sender.into_string(),
+                amount: redemption.amount,
+                juno_recipient: redemption.juno_recipient,
+                source_token: redemption.source_token,
+                status: redemption.status,
+            })
+        }
+        QueryMsg::GetVestingSchedule { source_token } => {
+            to_json_binary(&VESTING_SCHEDULES.may_load(deps.storage, &source_token)?)
+        }
+        QueryMsg::GetVesting { address } => to_json_binary(&query_vesting(deps, env, address)?),
+        QueryMsg::GetPauseStatus {} => {
+            let pause = PAUSE.may_load(deps.storage)?.unwrap_or_default();
+            to_json_binary(&PauseResponse {
+                paused: pause.paused,
+                reason: pause.reason,
+            })
+        }
+    }
+}
+
+fn query_vesting(deps: Deps, env: Env, address: String) -> StdResult<VestingResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let mut response = VestingResponse {
+        total: Uint128::zero(),
+        vested: Uint128::zero(),
+        unvested: Uint128::zero(),
+        claimed: Uint128::zero(),
+        claimable: Uint128::zero(),
+    };
+    for item in VESTINGS
+        .prefix(&address)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (_, vesting) = item?;
+        let vested = vesting.vested(env.block.time);
+        response.total += vesting.total;
+        response.vested += vested;
+        response.unvested += vesting.total - vested;
+        response.claimed += vesting.claimed;
+        response.claimable += vested - vesting.claimed;
     }
+    Ok(response)
+}
+
+fn query_mint_limits(deps: Deps, env: Env) -> StdResult<MintLimitsResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let total_supply = match &config.token_contract {
+        Some(token) => total_supply(deps, token)?,
+        None => Uint128::zero(),
+    };
+    let window_used = match &config.rate_limit {
+        Some(rate_limit) => window_usage(deps.storage, env.block.time, rate_limit)?,
+        None => Uint128::zero(),
+    };
+
+    Ok(MintLimitsResponse {
+        supply_cap: config.supply_cap,
+        total_supply,
+        remaining_supply: config
+            .supply_cap
+            .map(|cap| cap.saturating_sub(total_supply)),
+        window_remaining: config
+            .rate_limit
+            .as_ref()
+            .map(|limit| limit.max_amount.saturating_sub(window_used)),
+        rate_limit: config.rate_limit,
+        window_used,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cosmwasm_schema::cw_serde;
     use cosmwasm_std::Empty;
     use cw_multi_test::{App, Contract, ContractWrapper, Executor, IntoAddr};
+    use cw_storage_plus::Map;
+
+    const JUNO_MERGER: &str = "juno1ghd753shjuwexxywmgs4xz7x2q732vcnkm6h2pyv9s6ah3hylvrq722sry";
 
     fn contract_xion_minter() -> Box<dyn Contract<Empty>> {
-        let contract = ContractWrapper::new(execute, instantiate, query).with_reply(reply);
+        let contract = ContractWrapper::new(execute, instantiate, query)
+            .with_reply(reply)
+            .with_migrate(migrate);
         Box::new(contract)
     }
 
-    fn contract_cw20_base() -> Box<dyn Contract<Empty>> {
-        let contract = ContractWrapper::new(
-            cw20_base::contract::execute,
-            cw20_base::contract::instantiate,
-            cw20_base::contract::query,
-        );
+    fn mock_proxy_instantiate(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    fn mock_proxy_execute(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: serde_json::Value,
+    ) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    fn mock_proxy_query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+        to_json_binary("no queries")
+    }
+
+    /// Accepts any execute message, so it also stands in for the note.
+    fn contract_mock_proxy() -> Box<dyn Contract<Empty>> {
+        let contract =
+            ContractWrapper::new(mock_proxy_execute, mock_proxy_instantiate, mock_proxy_query);
         Box::new(contract)
     }
 
-    fn setup_app() -> (App, Addr, Addr, u64) {
-        let mut app = App::default();
+    /// Remote account each proxy executes for, as the mock voice records it.
+    const PROXY_SENDERS: Map<&Addr, String> = Map::new("proxy_senders");
 
-        let cw20_code_id = app.store_code(contract_cw20_base());
-        let minter_code_id = app.store_code(contract_xion_minter());
+    #[cw_serde]
+    struct RegisterProxy {
+        proxy: String,
+        remote_sender: String,
+    }
 
-        let minter = "the_minter".into_addr();
+    fn mock_voice_execute(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: RegisterProxy,
+    ) -> StdResult<Response> {
+        PROXY_SENDERS.save(
+            deps.storage,
+            &Addr::unchecked(msg.proxy),
+            &msg.remote_sender,
+        )?;
+        Ok(Response::new())
+    }
+
+    fn mock_voice_query(deps: Deps, _env: Env, msg: VoiceQueryMsg) -> StdResult<Binary> {
+        match msg {
+            VoiceQueryMsg::SenderInfoForProxy { proxy } => {
+                let sender = PROXY_SENDERS
+                    .may_load(deps.storage, &Addr::unchecked(proxy))?
+                    .map(|remote_sender| SenderInfo {
+                        connection_id: "connection-0".to_string(),
+                        remote_port: "wasm.juno_note".to_string(),
+                        remote_sender,
+                    });
+                to_json_binary(&sender)
+            }
+        }
+    }
+
+    fn contract_mock_voice() -> Box<dyn Contract<Empty>> {
+        let contract =
+            ContractWrapper::new(mock_voice_execute, mock_proxy_instantiate, mock_voice_query);
+        Box::new(contract)
+    }
+
+    /// Instantiates a proxy the way the voice does on a remote account's
+    /// first packet.
+    fn create_proxy(app: &mut App, voice: &Addr, remote_sender: &str) -> Addr {
+        let proxy_code_id = app.store_code(contract_mock_proxy());
+        let proxy = app
+            .instantiate_contract(proxy_code_id, voice.clone(), &Empty {}, &[], "proxy", None)
+            .unwrap();
+        app.execute_contract(
+            voice.clone(),
+            voice.clone(),
+            &RegisterProxy {
+                proxy: proxy.to_string(),
+                remote_sender: remote_sender.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        proxy
+    }
+
+    fn contract_cw20_base() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        );
+        Box::new(contract)
+    }
+
+    /// Returns the app, the guardian, the minter contract and an unbound
+    /// proxy instantiated by the voice.
+    fn setup_app() -> (App, Addr, Addr, Addr) {
+        setup_app_with_limits(None, None)
+    }
+
+    fn setup_app_with_limits(
+        supply_cap: Option<Uint128>,
+        rate_limit: Option<RateLimit>,
+    ) -> (App, Addr, Addr, Addr) {
+        let mut app = App::default();
+
+        let cw20_code_id = app.store_code(contract_cw20_base());
+        let minter_code_id = app.store_code(contract_xion_minter());
+        let voice_code_id = app.store_code(contract_mock_voice());
+
+        let guardian = "the_minter".into_addr();
+        let voice = app
+            .instantiate_contract(
+                voice_code_id,
+                guardian.clone(),
+                &Empty {},
+                &[],
+                "voice",
+                None,
+            )
+            .unwrap();
+        let proxy = create_proxy(&mut app, &voice, JUNO_MERGER);
 
         let minter_init_msg = InstantiateMsg {
             token_name: "Merger Token".to_string(),
             token_symbol: "MTKN".to_string(),
             token_decimals: 6,
-            cw20_code_id: cw20_code_id,
+            cw20_code_id,
+            guardian: None,
+            polytone_voice: voice.to_string(),
+            supply_cap,
+            rate_limit,
         };
 
         let minter_addr = app
             .instantiate_contract(
                 minter_code_id,
-                minter.clone(),
+                guardian.clone(),
                 &minter_init_msg,
                 &[],
                 "Xion Minter",
@@ -230,12 +1145,25 @@ mod tests {
             )
             .unwrap();
 
-        (app, minter, minter_addr, cw20_code_id)
+        (app, guardian, minter_addr, proxy)
+    }
+
+    fn bind_proxy(app: &mut App, guardian: &Addr, minter_addr: &Addr, proxy: &Addr) {
+        app.execute_contract(
+            guardian.clone(),
+            minter_addr.clone(),
+            &ExecuteMsg::BindProxy {
+                proxy: proxy.to_string(),
+                juno_merger: JUNO_MERGER.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
     }
 
     #[test]
     fn test_minter_instantiates_cw20() {
-        let (mut app, minter, minter_addr, _) = setup_app();
+        let (app, _, minter_addr, proxy) = setup_app();
 
         let config_resp: ConfigResponse = app
             .wrap()
@@ -243,6 +1171,9 @@ mod tests {
             .unwrap();
 
         assert_eq!(config_resp.minter, None);
+        assert_eq!(config_resp.juno_merger, None);
+        let proxy_info = app.wrap().query_wasm_contract_info(&proxy).unwrap();
+        assert_eq!(config_resp.voice, proxy_info.creator);
 
         let cw20_addr = config_resp.token_contract.expect("No Contract address set");
 
@@ -258,16 +1189,19 @@ mod tests {
 
     #[test]
     fn test_mint_tokens() {
-        let (mut app, minter, minter_addr, _) = setup_app();
+        let (mut app, guardian, minter_addr, proxy) = setup_app();
+        bind_proxy(&mut app, &guardian, &minter_addr, &proxy);
 
         let recipient = "recipient1".into_addr();
 
-        // Test successful mint by minter
+        // Test successful mint by the bound proxy
         let mint_msg = ExecuteMsg::Mint {
             amount: Uint128::new(1000),
             recipient: Some(recipient.to_string()),
+            nonce: 1,
+            source_token: None,
         };
-        app.execute_contract(minter.clone(), minter_addr.clone(), &mint_msg, &[])
+        app.execute_contract(proxy.clone(), minter_addr.clone(), &mint_msg, &[])
             .unwrap();
 
         let config_resp: ConfigResponse = app
@@ -287,21 +1221,34 @@ mod tests {
             )
             .unwrap();
         assert_eq!(balance.balance, Uint128::new(1000));
-        assert_eq!(config_resp.minter.unwrap(), minter.into_string());
+        assert_eq!(config_resp.minter.unwrap(), proxy.into_string());
+        assert_eq!(config_resp.juno_merger.unwrap(), JUNO_MERGER);
     }
 
     #[test]
     fn test_unauthorized_mint() {
-        let (mut app, _, minter, _) = setup_app();
+        let (mut app, guardian, minter, proxy) = setup_app();
         let unauthorized = "unauthorized".into_addr();
         let recipient = "recipient1".into_addr();
 
         let mint_msg = ExecuteMsg::Mint {
             amount: Uint128::new(1000),
             recipient: Some(recipient.to_string()),
+            nonce: 1,
+            source_token: None,
         };
 
-        app.execute_contract(minter.clone(), minter.clone(), &mint_msg, &[])
+        // nobody can mint before the guardian binds the proxy
+        let err = app
+            .execute_contract(unauthorized.clone(), minter.clone(), &mint_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::ProxyNotBound {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        bind_proxy(&mut app, &guardian, &minter, &proxy);
+        app.execute_contract(proxy, minter.clone(), &mint_msg, &[])
             .unwrap();
 
         // Test mint failure from unauthorized address
@@ -315,19 +1262,602 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_bind_proxy_requires_voice_instance() {
+        let (mut app, guardian, minter_addr, proxy) = setup_app();
+
+        let bind = |proxy: &Addr| ExecuteMsg::BindProxy {
+            proxy: proxy.to_string(),
+            juno_merger: JUNO_MERGER.to_string(),
+        };
+
+        let err = app
+            .execute_contract(proxy.clone(), minter_addr.clone(), &bind(&proxy), &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // a plain account is not a proxy
+        let account = "impostor".into_addr();
+        let err = app
+            .execute_contract(guardian.clone(), minter_addr.clone(), &bind(&account), &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidProxy { proxy } => assert_eq!(proxy, account.to_string()),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // neither is a contract someone other than the voice instantiated
+        let proxy_code_id = app.store_code(contract_mock_proxy());
+        let fake = app
+            .instantiate_contract(proxy_code_id, account, &Empty {}, &[], "fake", None)
+            .unwrap();
+        let err = app
+            .execute_contract(guardian.clone(), minter_addr.clone(), &bind(&fake), &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidProxy { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // nor a voice proxy executing for some other Juno account
+        let config: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&minter_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        let other = create_proxy(
+            &mut app,
+            &Addr::unchecked(config.voice),
+            "juno1qg5ega6dykkxc307y25pecuufrjkxkaggkkxh7nad0vhyhtuhw3seew7v3",
+        );
+        let err = app
+            .execute_contract(guardian.clone(), minter_addr.clone(), &bind(&other), &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::ProxySenderMismatch { proxy, juno_merger } => {
+                assert_eq!(
+                    (proxy, juno_merger),
+                    (other.to_string(), JUNO_MERGER.to_string())
+                )
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // juno_merger has to be a juno contract address
+        for juno_merger in [
+            "",
+            "juno1merger",
+            "xion1wkwy0xh89ksdgj9hr347dyd2dw7zesmtrue6kfzyml4vdtz6e5wsx90sn0",
+        ] {
+            let err = app
+                .execute_contract(
+                    guardian.clone(),
+                    minter_addr.clone(),
+                    &ExecuteMsg::BindProxy {
+                        proxy: proxy.to_string(),
+                        juno_merger: juno_merger.to_string(),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            match err.downcast::<ContractError>().unwrap() {
+                ContractError::InvalidJunoMerger { address } => assert_eq!(address, juno_merger),
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+
+        app.execute_contract(guardian, minter_addr, &bind(&proxy), &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_supply_cap_and_rate_limit() {
+        let (mut app, guardian, minter_addr, proxy) = setup_app_with_limits(
+            Some(Uint128::new(2500)),
+            Some(RateLimit {
+                max_amount: Uint128::new(1000),
+                window_seconds: 100,
+            }),
+        );
+        bind_proxy(&mut app, &guardian, &minter_addr, &proxy);
+
+        let mint = |nonce: u64, amount: u128| ExecuteMsg::Mint {
+            amount: Uint128::new(amount),
+            recipient: Some("recipient1".into_addr().to_string()),
+            nonce,
+            source_token: None,
+        };
+
+        app.execute_contract(proxy.clone(), minter_addr.clone(), &mint(1, 600), &[])
+            .unwrap();
+        let err = app
+            .execute_contract(proxy.clone(), minter_addr.clone(), &mint(2, 500), &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::RateLimitExceeded { remaining } => {
+                assert_eq!(remaining, Uint128::new(400))
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let limits: MintLimitsResponse = app
+            .wrap()
+            .query_wasm_smart(&minter_addr, &QueryMsg::GetMintLimits {})
+            .unwrap();
+        assert_eq!(limits.total_supply, Uint128::new(600));
+        assert_eq!(limits.remaining_supply, Some(Uint128::new(1900)));
+        assert_eq!(limits.window_used, Uint128::new(600));
+        assert_eq!(limits.window_remaining, Some(Uint128::new(400)));
+
+        // once the window rolls past the first mint its amount frees up
+        app.update_block(|block| block.time = block.time.plus_seconds(100));
+        app.execute_contract(proxy.clone(), minter_addr.clone(), &mint(2, 1000), &[])
+            .unwrap();
+
+        app.update_block(|block| block.time = block.time.plus_seconds(100));
+        let err = app
+            .execute_contract(proxy, minter_addr.clone(), &mint(3, 1000), &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::SupplyCapExceeded { remaining } => {
+                assert_eq!(remaining, Uint128::new(900))
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let err = app
+            .execute_contract(
+                guardian,
+                minter_addr,
+                &ExecuteMsg::UpdateRateLimit {
+                    rate_limit: Some(RateLimit {
+                        max_amount: Uint128::zero(),
+                        window_seconds: 100,
+                    }),
+                },
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidRateLimit {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_redeem_burns_and_refunds_failed_release() {
+        let (mut app, guardian, minter_addr, proxy) = setup_app();
+        bind_proxy(&mut app, &guardian, &minter_addr, &proxy);
+        let user = "user1".into_addr();
+
+        app.execute_contract(
+            proxy,
+            minter_addr.clone(),
+            &ExecuteMsg::Mint {
+                amount: Uint128::new(1000),
+                recipient: Some(user.to_string()),
+                nonce: 1,
+                source_token: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let config: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&minter_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        let token_addr = Addr::unchecked(config.token_contract.unwrap());
+
+        let redeem = |amount: u128| cw20::Cw20ExecuteMsg::Send {
+            contract: minter_addr.to_string(),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(&ReceiveMsg::Redeem {
+                juno_recipient: "juno1user".to_string(),
+                source_token: "juno1tokena".to_string(),
+            })
+            .unwrap(),
+        };
+
+        let err = app
+            .execute_contract(user.clone(), token_addr.clone(), &redeem(400), &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::ReverseBridgeNotConfigured {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let note_code_id = app.store_code(contract_mock_proxy());
+        let note = app
+            .instantiate_contract(note_code_id, guardian.clone(), &Empty {}, &[], "note", None)
+            .unwrap();
+        app.execute_contract(
+            guardian,
+            minter_addr.clone(),
+            &ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                polytone_note: Some(note.to_string()),
+            }),
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(user.clone(), token_addr.clone(), &redeem(400), &[])
+            .unwrap();
+        app.execute_contract(user.clone(), token_addr.clone(), &redeem(300), &[])
+            .unwrap();
+
+        let balance = |app: &App| -> Uint128 {
+            let resp: cw20::BalanceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    &token_addr,
+                    &cw20::Cw20QueryMsg::Balance {
+                        address: user.to_string(),
+                    },
+                )
+                .unwrap();
+            resp.balance
+        };
+        assert_eq!(balance(&app), Uint128::new(300));
+
+        let callback = |redemption_id: u64, result: Callback| {
+            ExecuteMsg::Callback(CallbackMessage {
+                initiator: minter_addr.clone(),
+                initiator_msg: to_json_binary(&redemption_id).unwrap(),
+                result,
+            })
+        };
+
+        let err = app
+            .execute_contract(
+                user.clone(),
+                minter_addr.clone(),
+                &callback(1, Callback::Execute(Err("timeout".to_string()))),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // the timed out release is minted back, the successful one stays burned
+        app.execute_contract(
+            note.clone(),
+            minter_addr.clone(),
+            &callback(1, Callback::Execute(Err("timeout".to_string()))),
+            &[],
+        )
+        .unwrap();
+        let released = Callback::Execute(Ok(polytone::callbacks::ExecutionResponse {
+            executed_by: "juno_proxy".to_string(),
+            result: vec![],
+        }));
+        app.execute_contract(note, minter_addr.clone(), &callback(2, released), &[])
+            .unwrap();
+
+        assert_eq!(balance(&app), Uint128::new(700));
+        let redemption: RedemptionResponse = app
+            .wrap()
+            .query_wasm_smart(&minter_addr, &QueryMsg::GetRedemption { redemption_id: 1 })
+            .unwrap();
+        assert_eq!(redemption.status, RedemptionStatus::Refunded);
+        let redemption: RedemptionResponse = app
+            .wrap()
+            .query_wasm_smart(&minter_addr, &QueryMsg::GetRedemption { redemption_id: 2 })
+            .unwrap();
+        assert_eq!(redemption.status, RedemptionStatus::Released);
+        assert_eq!(redemption.juno_recipient, "juno1user");
+    }
+
+    #[test]
+    fn test_duplicate_nonce_rejected() {
+        let (mut app, guardian, minter_addr, proxy) = setup_app();
+        bind_proxy(&mut app, &guardian, &minter_addr, &proxy);
+
+        let mint_msg = ExecuteMsg::Mint {
+            amount: Uint128::new(1000),
+            recipient: Some("recipient1".into_addr().to_string()),
+            nonce: 7,
+            source_token: None,
+        };
+        app.execute_contract(proxy.clone(), minter_addr.clone(), &mint_msg, &[])
+            .unwrap();
+
+        // a replayed packet carries the same nonce
+        let err = app
+            .execute_contract(proxy, minter_addr.clone(), &mint_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::NonceAlreadyProcessed { juno_merger, nonce } => {
+                assert_eq!(juno_merger, JUNO_MERGER);
+                assert_eq!(nonce, 7);
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let query_nonce = |nonce: u64| -> NonceResponse {
+            app.wrap()
+                .query_wasm_smart(
+                    &minter_addr,
+                    &QueryMsg::GetNonce {
+                        juno_merger: JUNO_MERGER.to_string(),
+                        nonce,
+                    },
+                )
+                .unwrap()
+        };
+        assert!(query_nonce(7).processed);
+        assert_eq!(query_nonce(7).height, Some(app.block_info().height));
+        assert!(!query_nonce(8).processed);
+    }
+
+    #[test]
+    fn test_vesting_mint_and_claim() {
+        let (mut app, guardian, minter_addr, proxy) = setup_app();
+        bind_proxy(&mut app, &guardian, &minter_addr, &proxy);
+        let recipient = "recipient1".into_addr();
+        let start = app.block_info().time;
+
+        let schedule = |cliff_seconds: u64, duration_seconds: u64| ExecuteMsg::SetVestingSchedule {
+            source_token: "juno1tokena".to_string(),
+            schedule: Some(VestingSchedule {
+                cliff_seconds,
+                duration_seconds,
+            }),
+        };
+        let err = app
+            .execute_contract(
+                proxy.clone(),
+                minter_addr.clone(),
+                &schedule(100, 1000),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let err = app
+            .execute_contract(
+                guardian.clone(),
+                minter_addr.clone(),
+                &schedule(2000, 1000),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidVestingSchedule {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        app.execute_contract(guardian, minter_addr.clone(), &schedule(100, 1000), &[])
+            .unwrap();
+
+        let mint = |nonce: u64, source_token: &str| ExecuteMsg::Mint {
+            amount: Uint128::new(1000),
+            recipient: Some(recipient.to_string()),
+            nonce,
+            source_token: Some(source_token.to_string()),
+        };
+        app.execute_contract(
+            proxy.clone(),
+            minter_addr.clone(),
+            &mint(1, "juno1tokena"),
+            &[],
+        )
+        .unwrap();
+        // tokens without a schedule mint straight through
+        app.execute_contract(proxy, minter_addr.clone(), &mint(2, "juno1tokenb"), &[])
+            .unwrap();
+
+        let config: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&minter_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        let cw20_addr = config.token_contract.unwrap();
+        let balance = |app: &App, address: &Addr| -> Uint128 {
+            let res: cw20::BalanceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw20_addr,
+                    &cw20::Cw20QueryMsg::Balance {
+                        address: address.to_string(),
+                    },
+                )
+                .unwrap();
+            res.balance
+        };
+        let vesting = |app: &App| -> VestingResponse {
+            app.wrap()
+                .query_wasm_smart(
+                    &minter_addr,
+                    &QueryMsg::GetVesting {
+                        address: recipient.to_string(),
+                    },
+                )
+                .unwrap()
+        };
+        assert_eq!(balance(&app, &recipient), Uint128::new(1000));
+        assert_eq!(balance(&app, &minter_addr), Uint128::new(1000));
+
+        // nothing vests before the cliff
+        app.update_block(|block| block.time = start.plus_seconds(50));
+        assert_eq!(vesting(&app).unvested, Uint128::new(1000));
+        let err = app
+            .execute_contract(
+                recipient.clone(),
+                minter_addr.clone(),
+                &ExecuteMsg::Claim {},
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::NothingToClaim {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        app.update_block(|block| block.time = start.plus_seconds(400));
+        let res = vesting(&app);
+        assert_eq!(res.vested, Uint128::new(400));
+        assert_eq!(res.claimable, Uint128::new(400));
+        app.execute_contract(
+            recipient.clone(),
+            minter_addr.clone(),
+            &ExecuteMsg::Claim {},
+            &[],
+        )
+        .unwrap();
+        assert_eq!(balance(&app, &recipient), Uint128::new(1400));
+        // the claim moves vested tokens from claimable to claimed
+        let res = vesting(&app);
+        assert_eq!(res.total, Uint128::new(1000));
+        assert_eq!(res.vested, Uint128::new(400));
+        assert_eq!(res.unvested, Uint128::new(600));
+        assert_eq!(res.claimed, Uint128::new(400));
+        assert_eq!(res.claimable, Uint128::zero());
+        assert_eq!(res.total, res.vested + res.unvested);
+        assert_eq!(res.vested, res.claimed + res.claimable);
+
+        app.update_block(|block| block.time = start.plus_seconds(1000));
+        app.execute_contract(
+            recipient.clone(),
+            minter_addr.clone(),
+            &ExecuteMsg::Claim {},
+            &[],
+        )
+        .unwrap();
+        assert_eq!(balance(&app, &recipient), Uint128::new(2000));
+        assert_eq!(balance(&app, &minter_addr), Uint128::zero());
+        assert_eq!(vesting(&app).total, Uint128::zero());
+    }
+
+    #[test]
+    fn test_batch_mint_is_atomic() {
+        let (mut app, guardian, minter_addr, proxy) =
+            setup_app_with_limits(Some(Uint128::new(2000)), None);
+        bind_proxy(&mut app, &guardian, &minter_addr, &proxy);
+        let alice = "alice".into_addr();
+        let bob = "bob".into_addr();
+
+        let entry = |recipient: &Addr, amount: u128, nonce: u64| MintEntry {
+            amount: Uint128::new(amount),
+            recipient: recipient.to_string(),
+            nonce,
+            source_token: None,
+        };
+        let batch = |entries: Vec<MintEntry>| ExecuteMsg::BatchMint { entries };
+
+        let err = app
+            .execute_contract(proxy.clone(), minter_addr.clone(), &batch(vec![]), &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::EmptyBatch {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let err = app
+            .execute_contract(
+                guardian,
+                minter_addr.clone(),
+                &batch(vec![entry(&alice, 100, 1)]),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        app.execute_contract(
+            proxy.clone(),
+            minter_addr.clone(),
+            &batch(vec![entry(&alice, 300, 1), entry(&bob, 500, 2)]),
+            &[],
+        )
+        .unwrap();
+
+        // a bad entry fails the whole batch, including entries before it
+        let err = app
+            .execute_contract(
+                proxy.clone(),
+                minter_addr.clone(),
+                &batch(vec![entry(&alice, 100, 3), entry(&bob, 100, 2)]),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidBatchEntry { index, .. } => assert_eq!(index, 1),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // the supply cap counts earlier entries of the same batch
+        let err = app
+            .execute_contract(
+                proxy,
+                minter_addr.clone(),
+                &batch(vec![entry(&alice, 700, 3), entry(&bob, 700, 4)]),
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::InvalidBatchEntry { index, reason } => {
+                assert_eq!(index, 1);
+                assert_eq!(
+                    reason,
+                    ContractError::SupplyCapExceeded {
+                        remaining: Uint128::new(500)
+                    }
+                    .to_string()
+                );
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let nonce: NonceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &minter_addr,
+                &QueryMsg::GetNonce {
+                    juno_merger: JUNO_MERGER.to_string(),
+                    nonce: 3,
+                },
+            )
+            .unwrap();
+        assert!(!nonce.processed);
+
+        let config: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&minter_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                config.token_contract.unwrap(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: alice.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(300));
+    }
+
     #[test]
     fn test_zero_amount_mint() {
-        let (mut app, minter, minter_addr, _) = setup_app();
+        let (mut app, guardian, minter_addr, proxy) = setup_app();
+        bind_proxy(&mut app, &guardian, &minter_addr, &proxy);
         let recipient = "recipient1".into_addr();
 
         let mint_msg = ExecuteMsg::Mint {
             amount: Uint128::zero(),
             recipient: Some(recipient.to_string()),
+            nonce: 1,
+            source_token: None,
         };
 
         // Test mint failure with zero amount
         let err = app
-            .execute_contract(minter, minter_addr.clone(), &mint_msg, &[])
+            .execute_contract(proxy, minter_addr.clone(), &mint_msg, &[])
             .unwrap_err();
 
         match err.downcast::<ContractError>().unwrap() {
@@ -335,4 +1865,106 @@ mod tests {
             e => panic!("unexpected error: {}", e),
         }
     }
+
+    #[test]
+    fn test_migrate_legacy_deployment() {
+        use cosmwasm_std::testing::{mock_dependencies, mock_env};
+
+        let mut deps = mock_dependencies();
+        crate::state::LEGACY_CONFIG
+            .save(
+                deps.as_mut().storage,
+                &crate::state::LegacyConfig {
+                    token_contract: Some(Addr::unchecked("merged_token")),
+                    minter: Some(Addr::unchecked("polytone_proxy")),
+                },
+            )
+            .unwrap();
+
+        let mut msg = MigrateMsg {
+            guardian: Some("guardian".to_string()),
+            polytone_voice: None,
+            juno_merger: None,
+        };
+        let err = migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap_err();
+        match err {
+            ContractError::MissingMigrateField { field } => assert_eq!(field, "polytone_voice"),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        msg.polytone_voice = Some("polytone_voice".to_string());
+        msg.juno_merger = Some(JUNO_MERGER.to_string());
+        migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap();
+
+        // the unchecked legacy minter has to be bound again
+        let config = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(config.guardian, Addr::unchecked("guardian"));
+        assert_eq!(config.voice, Addr::unchecked("polytone_voice"));
+        assert_eq!(config.juno_merger, Some(JUNO_MERGER.to_string()));
+        assert_eq!(config.token_contract, Some(Addr::unchecked("merged_token")));
+        assert_eq!(config.minter, None);
+
+        let version = cw2::get_contract_version(&deps.storage).unwrap();
+        assert_eq!(version.contract, CONTRACT_NAME);
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        // downgrades are refused
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+        match err {
+            ContractError::CannotMigrate {
+                previous_version, ..
+            } => assert_eq!(previous_version, "99.0.0"),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_pause_blocks_mint() {
+        let (mut app, guardian, minter_addr, proxy) = setup_app();
+        bind_proxy(&mut app, &guardian, &minter_addr, &proxy);
+        let recipient = "recipient1".into_addr();
+        let unauthorized = "unauthorized".into_addr();
+
+        let pause_msg = ExecuteMsg::Pause {
+            reason: Some("bridge incident".to_string()),
+        };
+        let err = app
+            .execute_contract(unauthorized, minter_addr.clone(), &pause_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        // the instantiator is the default guardian
+        app.execute_contract(guardian.clone(), minter_addr.clone(), &pause_msg, &[])
+            .unwrap();
+
+        let pause: PauseResponse = app
+            .wrap()
+            .query_wasm_smart(&minter_addr, &QueryMsg::GetPauseStatus {})
+            .unwrap();
+        assert!(pause.paused);
+        assert_eq!(pause.reason, Some("bridge incident".to_string()));
+
+        let mint_msg = ExecuteMsg::Mint {
+            amount: Uint128::new(1000),
+            recipient: Some(recipient.to_string()),
+            nonce: 1,
+            source_token: None,
+        };
+        let err = app
+            .execute_contract(proxy.clone(), minter_addr.clone(), &mint_msg, &[])
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Paused {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        app.execute_contract(guardian, minter_addr.clone(), &ExecuteMsg::Unpause {}, &[])
+            .unwrap();
+        app.execute_contract(proxy, minter_addr, &mint_msg, &[])
+            .unwrap();
+    }
 }
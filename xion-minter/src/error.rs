@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -17,5 +17,77 @@ pub enum ContractError {
     NoContractAddress {},
 
     #[error("InvalidAmount")]
-    InvalidAmount {}
+    InvalidAmount {},
+
+    #[error("Paused")]
+    Paused {},
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+
+    #[error("Cannot migrate from {previous_contract} {previous_version}")]
+    CannotMigrate {
+        previous_contract: String,
+        previous_version: String,
+    },
+
+    #[error("{field} is required to migrate a legacy deployment")]
+    MissingMigrateField { field: String },
+
+    #[error("No Polytone proxy has been bound")]
+    ProxyNotBound {},
+
+    #[error("Nonce {nonce} from {juno_merger} was already minted")]
+    NonceAlreadyProcessed { juno_merger: String, nonce: u64 },
+
+    #[error("{proxy} was not instantiated by the Polytone voice")]
+    InvalidProxy { proxy: String },
+
+    #[error("{proxy} does not execute for {juno_merger}")]
+    ProxySenderMismatch { proxy: String, juno_merger: String },
+
+    #[error("Invalid juno-merger address {address}: expected a juno contract")]
+    InvalidJunoMerger { address: String },
+
+    #[error("Supply cap exceeded, {remaining} left")]
+    SupplyCapExceeded { remaining: Uint128 },
+
+    #[error("Rate limit exceeded, {remaining} left in the current window")]
+    RateLimitExceeded { remaining: Uint128 },
+
+    #[error("Rate limit needs a non-zero amount and window")]
+    InvalidRateLimit {},
+
+    #[error("Only the merged token can be redeemed")]
+    InvalidToken {},
+
+    #[error("Invalid Juno recipient: {recipient}")]
+    InvalidRecipient { recipient: String },
+
+    #[error("Redemptions need a Polytone note and a bound juno-merger")]
+    ReverseBridgeNotConfigured {},
+
+    #[error("Batch has no entries")]
+    EmptyBatch {},
+
+    #[error("Batch entry {index} is invalid: {reason}")]
+    InvalidBatchEntry { index: u32, reason: String },
+
+    #[error("Vesting needs a non-zero duration no shorter than its cliff")]
+    InvalidVestingSchedule {},
+
+    #[error("Nothing has vested yet")]
+    NothingToClaim {},
+
+    #[error("Redemption {redemption_id} not found")]
+    RedemptionNotFound { redemption_id: u64 },
+
+    #[error("Redemption {redemption_id} was already settled")]
+    RedemptionAlreadySettled { redemption_id: u64 },
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
 }
\ No newline at end of file
@@ -1,7 +1,9 @@
-use crate::state::Config;
+use crate::state::{Config, RateLimit, RedemptionStatus, VestingSchedule};
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::Uint128;
 
+pub use fusogen_types::xion_minter::{ExecuteMsg, MintEntry, ReceiveMsg, UpdateConfigMsg};
+
 #[cw_serde]
 pub struct InstantiateMsg {
     pub token_name: String,
@@ -9,14 +11,25 @@ pub struct InstantiateMsg {
     pub token_decimals: u8,
     pub cw20_code_id: u64, //I'm not sure exactly how this works and how best to query this
                             //because it is the code Id of the deployed cw20 smart contract, I believe
+    /// May pause and unpause minting. Defaults to the instantiator.
+    pub guardian: Option<String>,
+    /// Polytone voice that instantiates proxies on this chain.
+    pub polytone_voice: String,
+    /// Hard cap on the merged token supply, also set as the CW20 cap.
+    pub supply_cap: Option<Uint128>,
+    pub rate_limit: Option<RateLimit>,
 }
 
 #[cw_serde]
-pub enum ExecuteMsg {
-    Mint {
-        amount: Uint128,
-        recipient: Option<String>,
-    },
+pub struct MigrateMsg {
+    /// Guardian to install when migrating a deployment that predates cw2
+    /// versioning. Ignored otherwise.
+    pub guardian: Option<String>,
+    /// Polytone voice to install when migrating a legacy deployment.
+    pub polytone_voice: Option<String>,
+    /// juno-merger to record when migrating a legacy deployment. Its
+    /// proxy still has to be bound through `BindProxy`.
+    pub juno_merger: Option<String>,
 }
 
 #[cw_serde]
@@ -24,10 +37,93 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     #[returns(Config)]
     GetConfig {},
+    #[returns(PauseResponse)]
+    GetPauseStatus {},
+    #[returns(MintLimitsResponse)]
+    GetMintLimits {},
+    #[returns(RedemptionResponse)]
+    GetRedemption { redemption_id: u64 },
+    /// Whether `nonce` from `juno_merger` was already minted.
+    #[returns(NonceResponse)]
+    GetNonce { juno_merger: String, nonce: u64 },
+    #[returns(Option<VestingSchedule>)]
+    GetVestingSchedule { source_token: String },
+    /// Totals across all of `address`'s vesting mints.
+    #[returns(VestingResponse)]
+    GetVesting { address: String },
 }
 
 #[cw_serde]
 pub struct ConfigResponse {
+    pub guardian: String,
+    pub voice: String,
+    pub juno_merger: Option<String>,
     pub minter: Option<String>,
     pub token_contract: Option<String>,
+    pub note: Option<String>,
+}
+
+#[cw_serde]
+pub struct MintLimitsResponse {
+    pub supply_cap: Option<Uint128>,
+    pub total_supply: Uint128,
+    /// `None` when there is no supply cap.
+    pub remaining_supply: Option<Uint128>,
+    pub rate_limit: Option<RateLimit>,
+    /// Amount minted inside the current window.
+    pub window_used: Uint128,
+    /// `None` when there is no rate limit.
+    pub window_remaining: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct NonceResponse {
+    pub processed: bool,
+    /// Block height of the mint, if processed.
+    pub height: Option<u64>,
+}
+
+#[cw_serde]
+pub struct VestingResponse {
+    /// Minted into vestings that aren't fully claimed yet; `vested` plus
+    /// `unvested`.
+    pub total: Uint128,
+    /// `claimed` plus `claimable`.
+    pub vested: Uint128,
+    pub unvested: Uint128,
+    pub claimed: Uint128,
+    /// Vested and not yet claimed.
+    pub claimable: Uint128,
+}
+
+/// Query understood by the Polytone voice, mirrored from polytone-voice.
+#[cw_serde]
+pub enum VoiceQueryMsg {
+    /// Returns `Option<SenderInfo>`, `None` if the voice didn't create
+    /// `proxy`.
+    SenderInfoForProxy { proxy: String },
+}
+
+/// The remote account a Polytone proxy executes for.
+#[cw_serde]
+pub struct SenderInfo {
+    pub connection_id: String,
+    pub remote_port: String,
+    pub remote_sender: String,
+}
+
+#[cw_serde]
+pub struct RedemptionResponse {
+    pub redemption_id: u64,
+    pub sender: String,
+    pub amount: Uint128,
+    pub juno_recipient: String,
+    pub source_token: String,
+    pub status: RedemptionStatus,
+}
+
+#[cw_serde]
+pub struct PauseResponse {
+    pub paused: bool,
+    pub reason: Option<String>,
 }
\ No newline at end of file
@@ -1,13 +1,103 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::Addr;
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Timestamp, Uint128};
+use cw_storage_plus::{Deque, Item, Map};
+
+pub use fusogen_types::xion_minter::{RateLimit, VestingSchedule};
 
 #[cw_serde]
 pub struct Config {
+    /// May pause and unpause minting.
+    pub guardian: Addr,
+    /// Polytone voice on XION; every legitimate proxy is instantiated by it.
+    pub voice: Addr,
+    /// juno-merger address on Juno whose proxy is bound as `minter`.
+    pub juno_merger: Option<String>,
+    pub token_contract: Option<Addr>,
+    /// Polytone proxy acting for `juno_merger`, set by the guardian through
+    /// `BindProxy`. Only it may mint.
+    pub minter: Option<Addr>,
+    /// Maximum total supply of the merged token. Mirrors the CW20 cap.
+    pub supply_cap: Option<Uint128>,
+    pub rate_limit: Option<RateLimit>,
+    /// Polytone note on XION used to reach juno-merger for redemptions.
+    pub note: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct MintRecord {
+    pub time: Timestamp,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct PauseState {
+    pub paused: bool,
+    pub reason: Option<String>,
+}
+
+#[cw_serde]
+pub enum RedemptionStatus {
+    /// Burned here, release on Juno not yet confirmed.
+    Pending,
+    Released,
+    /// The release failed or timed out and the burned tokens were minted
+    /// back to the sender.
+    Refunded,
+}
+
+#[cw_serde]
+pub struct Redemption {
+    pub sender: Addr,
+    pub amount: Uint128,
+    pub juno_recipient: String,
+    pub source_token: String,
+    pub status: RedemptionStatus,
+}
+
+/// Merged tokens held by this contract for a recipient until they vest.
+#[cw_serde]
+pub struct Vesting {
+    pub source_token: String,
+    pub total: Uint128,
+    pub claimed: Uint128,
+    pub start: Timestamp,
+    pub schedule: VestingSchedule,
+}
+
+impl Vesting {
+    /// Amount vested at `now`, claimed or not.
+    pub fn vested(&self, now: Timestamp) -> Uint128 {
+        let elapsed = now.seconds().saturating_sub(self.start.seconds());
+        if elapsed < self.schedule.cliff_seconds {
+            Uint128::zero()
+        } else if elapsed >= self.schedule.duration_seconds {
+            self.total
+        } else {
+            self.total
+                .multiply_ratio(elapsed, self.schedule.duration_seconds)
+        }
+    }
+}
+
+/// Config layout of deployments that predate cw2 versioning.
+#[cw_serde]
+pub struct LegacyConfig {
     pub token_contract: Option<Addr>,
-    pub minter: Option<Addr>  //this works as a first-come-first-served b/c I dont see how 
-                              //polytone's proxy can instantiate a contract, however the first mint execution call
-                              //irrevocably sets to the minter to the caller
+    pub minter: Option<Addr>,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+pub const PAUSE: Item<PauseState> = Item::new("pause");
+/// Mints inside the current rate limit window, oldest first.
+pub const MINT_WINDOW: Deque<MintRecord> = Deque::new("mint_window");
+/// (juno_merger, nonce) -> block height the nonce was minted at.
+pub const PROCESSED_NONCES: Map<(&str, u64), u64> = Map::new("processed_nonces");
+pub const REDEMPTION_COUNT: Item<u64> = Item::new("redemption_count");
+pub const REDEMPTIONS: Map<u64, Redemption> = Map::new("redemptions");
+/// Keyed by Juno source token.
+pub const VESTING_SCHEDULES: Map<&str, VestingSchedule> = Map::new("vesting_schedules");
+pub const VESTING_COUNT: Item<u64> = Item::new("vesting_count");
+/// (recipient, vesting id) -> vesting. Fully claimed entries are removed.
+pub const VESTINGS: Map<(&Addr, u64), Vesting> = Map::new("vestings");